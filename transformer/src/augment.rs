@@ -0,0 +1,462 @@
+//! Infers what a spec is missing from traffic captured in HAR files.
+//!
+//! Requests below `/api` that no operation documents become operations of a
+//! path template, with the segments holding a UUID or URN collapsed into
+//! parameters. Their bodies reference the schema of their media type when
+//! it's known, and otherwise have a schema inferred from the traffic.
+//! Properties that documented bodies have but their schemas lack are
+//! inferred too. The result is an OpenAPI Overlay marking everything it adds
+//! with `x-vcloud-inferred`, for review before it's merged into the spec.
+
+use crate::conformance::{api_path, header, Har};
+use crate::operation_ids::OperationIds;
+use crate::parsers::doc::operation::Method;
+use crate::schema_tweaks::path_parameters::{
+    path_parameter, path_parameter_name, path_template_parameters, UUID,
+};
+use crate::spec::{match_template, operations, SCHEMA_PREFIX};
+use crate::validate::{Validator, ViolationKind};
+use anyhow::Result;
+use openapiv3::{OpenAPI, ReferenceOr, Schema, SchemaKind};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+pub const INFERRED_EXTENSION: &str = "x-vcloud-inferred";
+
+lazy_static! {
+    static ref ID_SEGMENT: Regex = Regex::new(&format!("^urn:|{}", UUID)).unwrap();
+}
+
+/// The template of a path, with each segment holding a UUID or URN replaced
+/// by a parameter: `{id}`, then `{id2}` and so on.
+pub fn infer_template(path: &str) -> String {
+    let mut parameters = 0;
+    path.split('/')
+        .map(|segment| {
+            if !ID_SEGMENT.is_match(segment) {
+                return segment.to_string();
+            }
+            parameters += 1;
+            match parameters {
+                1 => "{id}".to_string(),
+                n => format!("{{id{}}}", n),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A JSON schema describing a value.
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_f64() => json!({"type": "number"}),
+        Value::Number(_) => json!({"type": "integer"}),
+        Value::String(_) => json!({"type": "string"}),
+        Value::Array(items) => json!({
+            "type": "array",
+            "items": items
+                .iter()
+                .map(infer_schema)
+                .reduce(merge_schemas)
+                .unwrap_or_else(|| json!({})),
+        }),
+        Value::Object(object) => json!({
+            "type": "object",
+            "properties": object
+                .iter()
+                .map(|(name, value)| (name.clone(), infer_schema(value)))
+                .collect::<Map<_, _>>(),
+        }),
+    }
+}
+
+/// A schema describing the values of both schemas. Objects get the
+/// properties of both, and values of different types are left unconstrained.
+fn merge_schemas(a: Value, b: Value) -> Value {
+    match (a["type"].as_str(), b["type"].as_str()) {
+        (None, _) if a.get("$ref").is_none() && a.as_object().is_some_and(Map::is_empty) => b,
+        (_, None) if b.get("$ref").is_none() && b.as_object().is_some_and(Map::is_empty) => a,
+        (Some("integer"), Some("number")) | (Some("number"), Some("integer")) => {
+            json!({"type": "number"})
+        }
+        (Some("array"), Some("array")) => json!({
+            "type": "array",
+            "items": merge_schemas(a["items"].clone(), b["items"].clone()),
+        }),
+        (Some("object"), Some("object")) => {
+            let mut properties = a["properties"].as_object().cloned().unwrap_or_default();
+            for (name, schema) in b["properties"].as_object().into_iter().flatten() {
+                let merged = match properties.remove(name) {
+                    Some(existing) => merge_schemas(existing, schema.clone()),
+                    None => schema.clone(),
+                };
+                properties.insert(name.clone(), merged);
+            }
+            json!({"type": "object", "properties": properties})
+        }
+        _ if a == b => a,
+        _ => json!({}),
+    }
+}
+
+fn inferred(mut schema: Value) -> Value {
+    if let Value::Object(object) = &mut schema {
+        object.insert(INFERRED_EXTENSION.into(), json!(true));
+    }
+    schema
+}
+
+/// What the traffic showed of an undocumented operation.
+#[derive(Default)]
+struct Operation {
+    /// The schemas of request bodies by content type.
+    request: BTreeMap<String, Value>,
+    /// The schemas of response bodies by status and content type. Bodies
+    /// without a content type are under the empty string.
+    responses: BTreeMap<u16, BTreeMap<String, Value>>,
+}
+
+/// Collects what the traffic shows the spec lacks.
+struct Augmenter<'a> {
+    spec: &'a OpenAPI,
+    validator: Validator<'a>,
+    /// Undocumented operations by path template and method.
+    operations: BTreeMap<(String, Method), Operation>,
+    /// Undocumented properties by schema reference.
+    properties: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl<'a> Augmenter<'a> {
+    /// The schema of a body: a reference to its media type's schema, one
+    /// inferred from JSON, or none.
+    fn body_schema(&self, content_type: &str, text: &str) -> Value {
+        match (
+            self.validator.schema_reference(content_type),
+            serde_json::from_str::<Value>(text),
+        ) {
+            (Some(reference), _) => json!({ "$ref": reference }),
+            (None, Ok(body)) if content_type.contains("json") => inferred(infer_schema(&body)),
+            _ => Value::Null,
+        }
+    }
+
+    fn add(&mut self, entry: &crate::conformance::Entry) {
+        let path = match api_path(&entry.request.url) {
+            Some(path) => path,
+            None => return,
+        };
+        let method = match entry.request.method.to_ascii_uppercase().parse::<Method>() {
+            Ok(method) => method,
+            Err(_) => return,
+        };
+        let template = match_template(path, &self.spec.paths);
+        let documented = template
+            .and_then(|t| self.spec.paths.paths.get(t)?.as_item())
+            .and_then(|item| {
+                operations(item).into_iter().find_map(|(m, operation)| {
+                    if m.eq_ignore_ascii_case(method.as_str()) {
+                        operation
+                    } else {
+                        None
+                    }
+                })
+            })
+            .is_some();
+        let request = entry
+            .request
+            .post_data
+            .as_ref()
+            .filter(|p| !p.text.is_empty())
+            .map(|post_data| {
+                let content_type = header(&entry.request.headers, "content-type")
+                    .unwrap_or(&post_data.mime_type)
+                    .to_string();
+                (content_type, post_data.text.as_str())
+            });
+        let response = entry
+            .response
+            .content
+            .text
+            .as_deref()
+            .filter(|t| !t.is_empty() && entry.response.content.encoding.is_none())
+            .map(|text| {
+                let content_type = header(&entry.response.headers, "content-type")
+                    .unwrap_or(&entry.response.content.mime_type)
+                    .to_string();
+                (content_type, text)
+            });
+
+        if documented {
+            for (content_type, text) in request.into_iter().chain(response) {
+                self.add_properties(&content_type, text);
+            }
+            return;
+        }
+
+        let template = template.map_or_else(|| infer_template(path), String::from);
+        let request = request.map(|(content_type, text)| {
+            let schema = self.body_schema(&content_type, text);
+            (content_type, schema)
+        });
+        let response = response.map(|(content_type, text)| {
+            let schema = self.body_schema(&content_type, text);
+            (content_type, schema)
+        });
+        let operation = self.operations.entry((template, method)).or_default();
+        if let Some((content_type, schema)) = request {
+            merge_into(&mut operation.request, content_type, schema);
+        }
+        let responses = operation
+            .responses
+            .entry(entry.response.status)
+            .or_default();
+        if let Some((content_type, schema)) = response {
+            merge_into(responses, content_type, schema);
+        }
+    }
+
+    /// Notes the properties a documented JSON body has that its schemas lack.
+    fn add_properties(&mut self, content_type: &str, text: &str) {
+        let body = match serde_json::from_str::<Value>(text) {
+            Ok(body) if content_type.contains("json") => body,
+            _ => return,
+        };
+        for violation in self.validator.validate(content_type, &body) {
+            if let (ViolationKind::Unexpected, Some(schema), Some(property)) =
+                (violation.kind, violation.schema, violation.property)
+            {
+                let value = match body
+                    .pointer(&violation.pointer)
+                    .and_then(|o| o.get(&property))
+                {
+                    Some(value) => value,
+                    None => continue,
+                };
+                merge_into(
+                    self.properties.entry(schema).or_default(),
+                    property,
+                    infer_schema(value),
+                );
+            }
+        }
+    }
+
+    /// The overlay actions adding what was collected.
+    fn actions(mut self) -> Result<Vec<Value>> {
+        let mut ids = OperationIds::new()?;
+        ids.reserve(
+            self.spec
+                .paths
+                .paths
+                .values()
+                .filter_map(ReferenceOr::as_item)
+                .flat_map(|item| operations(item).into_iter().filter_map(|(_, o)| o))
+                .filter_map(|o| o.operation_id.clone()),
+        );
+        for (template, method) in self.operations.keys() {
+            ids.register(*method, template, "");
+        }
+        let parameters = self
+            .spec
+            .components
+            .as_ref()
+            .map(|c| &c.parameters)
+            .cloned()
+            .unwrap_or_default();
+        let mut actions = Vec::new();
+        for ((template, method), operation) in std::mem::take(&mut self.operations) {
+            let parameters = path_template_parameters(&template)
+                .into_iter()
+                .map(|(segment, name)| {
+                    let reference = path_parameter_name(segment, name);
+                    if parameters.contains_key(&reference) {
+                        json!({ "$ref": format!("#/components/parameters/{}", reference) })
+                    } else {
+                        json!(path_parameter(segment, name, None))
+                    }
+                })
+                .collect::<Vec<_>>();
+            let mut documented = json!({
+                "operationId": ids.assign(method, &template, ""),
+                "description": "Inferred from captured traffic.",
+                INFERRED_EXTENSION: true,
+                "responses": operation
+                    .responses
+                    .into_iter()
+                    .map(|(status, content)| {
+                        let mut response = json!({"description": "Inferred from captured traffic."});
+                        if !content.is_empty() {
+                            response["content"] = content_json(content);
+                        }
+                        (status.to_string(), response)
+                    })
+                    .collect::<Map<_, _>>(),
+            });
+            if !parameters.is_empty() {
+                documented["parameters"] = json!(parameters);
+            }
+            if !operation.request.is_empty() {
+                documented["requestBody"] = json!({ "content": content_json(operation.request) });
+            }
+            actions.push(json!({
+                "target": "$.paths",
+                "description": format!("Add {} {}", method.as_str(), template),
+                "update": {
+                    template: {method.as_str().to_ascii_lowercase(): documented}
+                },
+            }));
+        }
+        for (reference, properties) in std::mem::take(&mut self.properties) {
+            let name = reference.trim_start_matches(SCHEMA_PREFIX);
+            let target = match properties_target(self.spec, name) {
+                Some(target) => target,
+                None => continue,
+            };
+            actions.push(json!({
+                "target": target,
+                "description": format!("Add properties to {}", name),
+                "update": properties
+                    .into_iter()
+                    .map(|(property, schema)| (property, inferred(schema)))
+                    .collect::<Map<_, _>>(),
+            }));
+        }
+        Ok(actions)
+    }
+}
+
+/// Where in the spec a schema's own properties are: the schema's, or
+/// those of the last object of its `allOf`.
+fn properties_target(spec: &OpenAPI, name: &str) -> Option<String> {
+    let schema = match spec.components.as_ref()?.schemas.get(name)? {
+        ReferenceOr::Item(schema) => schema,
+        ReferenceOr::Reference { .. } => return None,
+    };
+    let base = format!("$.components.schemas['{}']", name);
+    match &schema.schema_kind {
+        SchemaKind::AllOf { all_of } => all_of
+            .iter()
+            .rposition(|part| {
+                matches!(
+                    part,
+                    ReferenceOr::Item(Schema {
+                        schema_kind: SchemaKind::Type(openapiv3::Type::Object(_)),
+                        ..
+                    })
+                )
+            })
+            .map(|i| format!("{}.allOf[{}].properties", base, i)),
+        _ => Some(format!("{}.properties", base)),
+    }
+}
+
+fn merge_into(schemas: &mut BTreeMap<String, Value>, key: String, schema: Value) {
+    let merged = match schemas.remove(&key) {
+        Some(existing) if existing.is_null() => schema,
+        Some(existing) if existing.get("$ref").is_some() || schema.is_null() => existing,
+        Some(existing) => inferred(merge_schemas(existing, schema)),
+        None => schema,
+    };
+    schemas.insert(key, merged);
+}
+
+fn content_json(content: BTreeMap<String, Value>) -> Value {
+    content
+        .into_iter()
+        .map(|(content_type, schema)| {
+            let content_type = if content_type.is_empty() {
+                "application/octet-stream".to_string()
+            } else {
+                content_type
+            };
+            let media_type = if schema.is_null() {
+                json!({})
+            } else {
+                json!({ "schema": schema })
+            };
+            (content_type, media_type)
+        })
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// An OpenAPI Overlay adding what the captures show the spec lacks.
+pub fn augment(spec: &OpenAPI, hars: &[Har]) -> Result<Value> {
+    let mut augmenter = Augmenter {
+        spec,
+        validator: Validator::new(spec),
+        operations: BTreeMap::new(),
+        properties: BTreeMap::new(),
+    };
+    for entry in hars.iter().flat_map(|har| &har.log.entries) {
+        augmenter.add(entry);
+    }
+    Ok(json!({
+        "overlay": "1.0.0",
+        "info": {
+            "title": format!("Inferred additions to {}", spec.info.title),
+            "version": spec.info.version,
+        },
+        "actions": augmenter.actions()?,
+    }))
+}
+
+#[test]
+fn infer_template_test() {
+    assert_eq!(
+        infer_template(
+            "/vApp/vapp-a1b2c3d4-0000-4000-8000-123456789abc/widget/urn:vcloud:widget:1"
+        ),
+        "/vApp/{id}/widget/{id2}"
+    );
+    assert_eq!(infer_template("/org"), "/org");
+}
+
+#[test]
+fn augment_test() {
+    use crate::conformance::entry;
+    let spec: OpenAPI = serde_json::from_str(include_str!("codegen/test_spec.json")).unwrap();
+    let vapp = "application/vnd.vmware.vcloud.vApp+json;version=36.0";
+    let widget = "https://vcd.example.com/api/widget/urn:vcloud:widget";
+    let mut delete = entry(
+        "DELETE",
+        "https://vcd.example.com/api/query",
+        204,
+        "",
+        Value::Null,
+    );
+    delete["response"]["content"]["text"] = json!("");
+    let har: Har = serde_json::from_value(json!({"log": {"entries": [
+        entry("GET", "https://vcd.example.com/api/vApp/vapp-1", 200, vapp, json!({"vdc": {"bogus": 1}})),
+        entry("GET", &format!("{}:1", widget), 200, "application/json", json!({"size": 1})),
+        entry("GET", &format!("{}:2", widget), 200, "application/json", json!({"size": 2.5, "name": "w"})),
+        delete,
+    ]}}))
+    .unwrap();
+
+    let overlay = augment(&spec, &[har]).unwrap();
+    let actions = overlay["actions"].as_array().unwrap();
+    assert_eq!(actions.len(), 3);
+    assert_eq!(
+        actions[0]["update"]["/query"]["delete"]["responses"],
+        json!({"204": {"description": "Inferred from captured traffic."}})
+    );
+    let widget = &actions[1]["update"]["/widget/{id}"]["get"];
+    assert_eq!(widget[INFERRED_EXTENSION], true);
+    assert_eq!(widget["parameters"][0]["name"], "id");
+    assert_eq!(
+        widget["responses"]["200"]["content"]["application/json"]["schema"]["properties"],
+        json!({"name": {"type": "string"}, "size": {"type": "number"}})
+    );
+    assert_eq!(
+        actions[2],
+        json!({
+            "target": "$.components.schemas['vcloud_ContainerType'].properties",
+            "description": "Add properties to vcloud_ContainerType",
+            "update": {"bogus": {"type": "integer", INFERRED_EXTENSION: true}}
+        })
+    );
+}
@@ -0,0 +1,292 @@
+//! The `client` module of a generated crate: a runtime for sessions, versions,
+//! pages and tasks, and a method for each operation of the spec.
+
+use super::rust::{render_doc, snake_case, unique_in, RustType};
+use crate::spec::bare_media_type;
+use openapiv3::{OpenAPI, Operation, Parameter, ReferenceOr, StatusCode};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+const RUNTIME: &str = include_str!("client_runtime.rs");
+
+/// The methods the runtime defines, which operations mustn't be named after.
+const RUNTIME_METHODS: [&str; 14] = [
+    "accept",
+    "all_pages",
+    "call",
+    "login",
+    "logout",
+    "negotiate_version",
+    "new",
+    "request",
+    "send",
+    "set_poll_interval",
+    "set_tenant_context",
+    "set_version",
+    "version",
+    "wait_for_task",
+];
+
+/// The type of the JSON media types in `content`, if they all share one.
+fn content_type<'a>(
+    content: impl Iterator<Item = (&'a String, &'a openapiv3::MediaType)>,
+    resolve: &impl Fn(&str) -> RustType,
+) -> (Vec<&'a str>, RustType) {
+    let mut media_types = Vec::new();
+    let mut types = Vec::new();
+    for (media_type, content) in content.filter(|(m, _)| m.contains("json")) {
+        media_types.push(bare_media_type(media_type));
+        types.push(match &content.schema {
+            Some(ReferenceOr::Reference { reference }) => resolve(reference),
+            _ => RustType::Value,
+        });
+    }
+    let r#type = match types.split_first() {
+        Some((first, rest)) if rest.iter().all(|t| t == first) => first.clone(),
+        Some(_) => RustType::Value,
+        None => RustType::Primitive("()"),
+    };
+    (media_types, r#type)
+}
+
+fn render_strs(strs: &[&str]) -> String {
+    format!(
+        "&[{}]",
+        strs.iter()
+            .map(|s| format!("{:?}", s))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn operation(
+    out: &mut String,
+    names: &mut BTreeSet<String>,
+    method: &str,
+    path: &str,
+    operation: &Operation,
+    resolve: &impl Fn(&str) -> RustType,
+) {
+    let name = unique_in(
+        names,
+        snake_case(operation.operation_id.as_deref().unwrap_or(&format!(
+            "{}_{}",
+            method.to_ascii_lowercase(),
+            path
+        ))),
+    );
+    let mut arguments = Vec::new();
+    let mut path_format = String::new();
+    let mut path_arguments = Vec::new();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        path_format.push('/');
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(parameter) => {
+                let argument = snake_case(parameter);
+                path_format.push_str("{}");
+                arguments.push(format!("{}: &str", argument));
+                path_arguments.push(argument);
+            }
+            None => path_format.push_str(segment),
+        }
+    }
+    let has_query = operation
+        .parameters
+        .iter()
+        .any(|p| matches!(p, ReferenceOr::Item(Parameter::Query { .. })));
+    if has_query {
+        arguments.push("query: &[(&str, &str)]".into());
+    }
+    let body = match &operation.request_body {
+        Some(ReferenceOr::Item(body)) => {
+            let (media_types, r#type) = content_type(body.content.iter(), resolve);
+            let content_type = media_types
+                .first()
+                .copied()
+                .or_else(|| body.content.keys().next().map(|k| bare_media_type(k)));
+            let r#type = match r#type {
+                RustType::Primitive("()") => RustType::Value,
+                r#type => r#type,
+            };
+            if content_type.is_some() {
+                arguments.push(format!("body: &{}", r#type.render()));
+            }
+            content_type
+        }
+        _ => None,
+    };
+    let (accept, response) = operation
+        .responses
+        .responses
+        .iter()
+        .find(|(status, _)| match status {
+            StatusCode::Code(code) => (200..300).contains(code),
+            StatusCode::Range(range) => *range == 2,
+        })
+        .and_then(|(_, response)| match response {
+            ReferenceOr::Item(response) => Some(content_type(response.content.iter(), resolve)),
+            ReferenceOr::Reference { .. } => None,
+        })
+        .unwrap_or((Vec::new(), RustType::Primitive("()")));
+    let accept = if accept.is_empty() {
+        vec!["application/*+json"]
+    } else {
+        accept
+    };
+
+    out.push('\n');
+    render_doc(out, "    ", &operation.description);
+    if operation.deprecated {
+        out.push_str("    #[deprecated]\n");
+    }
+    writeln!(
+        out,
+        "    pub fn {}(&self{}) -> Result<{}, Error> {{",
+        name,
+        arguments
+            .iter()
+            .map(|a| format!(", {}", a))
+            .collect::<String>(),
+        response.render()
+    )
+    .unwrap();
+    let path = if path_arguments.is_empty() {
+        format!("{:?}", path_format)
+    } else {
+        format!("&format!({:?}, {})", path_format, path_arguments.join(", "))
+    };
+    let query = if has_query { "query" } else { "&[]" };
+    match body {
+        Some(content_type) => writeln!(
+            out,
+            "        self.send({:?}, {}, {}, {}, {:?}, body)",
+            method,
+            path,
+            query,
+            render_strs(&accept),
+            content_type
+        ),
+        None => writeln!(
+            out,
+            "        self.request({:?}, {}, {}, {})",
+            method,
+            path,
+            query,
+            render_strs(&accept)
+        ),
+    }
+    .unwrap();
+    out.push_str("    }\n");
+}
+
+/// The source of `src/client.rs`, naming schemas with `resolve`.
+pub(crate) fn client_rs(spec: &OpenAPI, resolve: impl Fn(&str) -> RustType) -> String {
+    let mut client = RUNTIME.to_string();
+    client.push_str("\nimpl Client {");
+    let mut names = RUNTIME_METHODS.iter().map(|m| m.to_string()).collect();
+    for (path, path_item) in &spec.paths.paths {
+        let path_item = match path_item {
+            ReferenceOr::Item(path_item) => path_item,
+            ReferenceOr::Reference { .. } => continue,
+        };
+        let operations = [
+            ("GET", &path_item.get),
+            ("PUT", &path_item.put),
+            ("POST", &path_item.post),
+            ("DELETE", &path_item.delete),
+            ("PATCH", &path_item.patch),
+        ];
+        for (method, o) in operations {
+            if let Some(o) = o {
+                operation(&mut client, &mut names, method, path, o, &resolve);
+            }
+        }
+    }
+    client.push_str("}\n");
+    client
+}
+
+#[test]
+fn client_test() {
+    let files = super::rust::generate(&super::rust::test_spec(), "test-models");
+    let client = &files["src/client.rs"];
+    assert!(client.contains(
+        "    /// Retrieve a vApp.
+    pub fn get_v_app(&self, id: &str) -> Result<VcloudVAppType, Error> {
+        self.request(\"GET\", &format!(\"/vApp/{}\", id), &[], &[\"application/vnd.vmware.vcloud.vApp+json\"])
+    }"
+    ));
+    assert!(client.contains(
+        "    pub fn update_v_app(&self, id: &str, body: &VcloudVAppType) -> Result<VcloudTaskType, Error> {
+        self.send(\"PUT\", &format!(\"/vApp/{}\", id), &[], &[\"application/vnd.vmware.vcloud.task+json\"], \"application/vnd.vmware.vcloud.vApp+json\", body)
+    }"
+    ));
+    assert!(client.contains(
+        "    pub fn query(&self, query: &[(&str, &str)]) -> Result<QueryResultRecords, Error> {"
+    ));
+    assert!(client.contains(
+        "    #[deprecated]
+    pub fn delete_session(&self) -> Result<(), Error> {
+        self.request(\"DELETE\", \"/session\", &[], &[\"application/*+json\"])
+    }"
+    ));
+    assert!(files["src/lib.rs"].contains("pub const API_VERSION: &str = \"36.0\";"));
+}
+
+#[test]
+#[ignore = "builds a generated crate, fetching its dependencies"]
+fn client_mock_server_test() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api/", listener.local_addr().unwrap());
+    let scenario = serde_json::from_value(serde_json::json!({"entities": {
+        "/vApp/vapp-1": {"name": "web"},
+        "/query": {"name": "vm", "page": 1, "record": []}
+    }}))
+    .unwrap();
+    let mock = crate::mock::Mock::new(super::rust::test_spec()).with_scenario(scenario);
+    std::thread::spawn(move || mock.serve(listener));
+    let files = super::rust::generate(&super::rust::test_spec(), "test-models");
+    super::run_example(
+        &files,
+        "mock_client",
+        MOCK_CLIENT_EXAMPLE,
+        &["client"],
+        &[("MOCK_URL", &url)],
+    );
+}
+
+/// A program driving the client against the mock at `MOCK_URL`, which
+/// refuses requests without a session token or of another version.
+#[cfg(test)]
+const MOCK_CLIENT_EXAMPLE: &str = r#"use std::time::Duration;
+use test_models::client::{Client, Error};
+
+fn main() {
+    let mut client = Client::new(&std::env::var("MOCK_URL").unwrap());
+    assert!(matches!(client.get_v_app("vapp-1"), Err(Error::Api { status: 401, .. })));
+    client.login("admin", "System", "secret").unwrap();
+
+    client.set_version("35.0");
+    assert!(matches!(client.get_v_app("vapp-1"), Err(Error::Api { status: 406, .. })));
+    client.set_version("36.0");
+
+    let mut vapp = client.get_v_app("vapp-1").unwrap();
+    assert_eq!(vapp.name, "web");
+
+    let pages: Vec<test_models::QueryResultRecords> = client
+        .all_pages("/query", &[("type", "vm")], &["application/vnd.vmware.vcloud.query.records+json"])
+        .unwrap();
+    assert_eq!(pages.len(), 1);
+
+    vapp.name = "db".into();
+    let task = client.update_v_app("vapp-1", &vapp).unwrap();
+    client.set_poll_interval(Duration::from_millis(10));
+    let task: test_models::VcloudTaskType = client
+        .wait_for_task(task.href.as_deref().unwrap(), Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(task.status.as_deref(), Some("success"));
+    assert_eq!(client.get_v_app("vapp-1").unwrap().name, "db");
+
+    client.logout().unwrap();
+}
+"#;
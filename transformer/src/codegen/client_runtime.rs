@@ -0,0 +1,317 @@
+//! A blocking client for the API.
+//!
+//! It logs in, negotiates the API version, sets the tenant context, follows
+//! `nextPage` links and waits for tasks. Each operation of the spec is a
+//! method of [`Client`] taking its path parameters and body.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use crate::*;
+
+/// What went wrong talking to the API.
+#[derive(Debug)]
+pub enum Error {
+    /// The API answered with an error status.
+    Api { status: u16, message: String },
+    /// The request didn't get an answer.
+    Transport(String),
+    /// The answer wasn't the JSON expected.
+    Json(String),
+    /// The server supports none of the versions this client knows.
+    UnsupportedVersion(Vec<String>),
+    /// A task ended without succeeding.
+    Task { status: String, message: String },
+    /// A task didn't end in time.
+    Timeout,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Api { status, message } => write!(f, "API error {}: {}", status, message),
+            Error::Transport(message) => write!(f, "transport error: {}", message),
+            Error::Json(message) => write!(f, "unexpected JSON: {}", message),
+            Error::UnsupportedVersion(versions) => {
+                write!(f, "no supported API version among {:?}", versions)
+            }
+            Error::Task { status, message } => write!(f, "task {}: {}", status, message),
+            Error::Timeout => write!(f, "timed out waiting for task"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::Status(status, response) => {
+                let body = response.into_string().unwrap_or_default();
+                let message = serde_json::from_str::<Value>(&body)
+                    .ok()
+                    .and_then(|v| v.get("message")?.as_str().map(String::from))
+                    .unwrap_or(body);
+                Error::Api { status, message }
+            }
+            ureq::Error::Transport(transport) => Error::Transport(transport.to_string()),
+        }
+    }
+}
+
+/// Statuses after which a task doesn't change any more.
+const TASK_DONE: [&str; 4] = ["success", "error", "canceled", "aborted"];
+
+pub struct Client {
+    agent: ureq::Agent,
+    /// The API's root, such as `https://vcloud.example.com/api`.
+    base_url: String,
+    version: String,
+    /// The header and value authenticating requests, once logged in.
+    authorization: Option<(&'static str, String)>,
+    /// The id of the organization requests act in, for system administrators.
+    tenant: Option<String>,
+    poll_interval: Duration,
+}
+
+impl Client {
+    /// A client of the API at `base_url`, speaking the spec's version.
+    pub fn new(base_url: &str) -> Self {
+        Client {
+            agent: ureq::AgentBuilder::new().build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            version: API_VERSION.to_string(),
+            authorization: None,
+            tenant: None,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn set_version(&mut self, version: &str) {
+        self.version = version.to_string();
+    }
+
+    pub fn set_tenant_context(&mut self, org_id: Option<&str>) {
+        self.tenant = org_id.map(String::from);
+    }
+
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Picks the spec's version if the server supports it, or else the newest
+    /// older one it does.
+    pub fn negotiate_version(&mut self) -> Result<&str, Error> {
+        let versions = self
+            .agent
+            .get(&format!("{}/versions", self.base_url))
+            .set("Accept", "application/*+json")
+            .call()?
+            .into_json::<Value>()
+            .map_err(|e| Error::Json(e.to_string()))?;
+        let versions = versions["versionInfo"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|v| v["deprecated"] != Value::Bool(true))
+            .filter_map(|v| v["version"].as_str().map(String::from))
+            .collect::<Vec<_>>();
+        let known = version_key(API_VERSION);
+        match versions
+            .iter()
+            .filter(|v| version_key(v) <= known)
+            .max_by_key(|v| version_key(v))
+        {
+            Some(version) => {
+                self.version = version.clone();
+                Ok(&self.version)
+            }
+            None => Err(Error::UnsupportedVersion(versions)),
+        }
+    }
+
+    /// Logs in as `user` of `org`, keeping the token for later requests.
+    pub fn login(&mut self, user: &str, org: &str, password: &str) -> Result<(), Error> {
+        use base64::Engine;
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}@{}:{}", user, org, password));
+        let response = self
+            .agent
+            .post(&format!("{}/sessions", self.base_url))
+            .set("Accept", &self.accept(&["application/*+json"]))
+            .set("Authorization", &format!("Basic {}", credentials))
+            .call()?;
+        self.authorization = match (
+            response.header("X-VMWARE-VCLOUD-ACCESS-TOKEN"),
+            response.header("x-vcloud-authorization"),
+        ) {
+            (Some(token), _) => Some(("Authorization", format!("Bearer {}", token))),
+            (None, Some(token)) => Some(("x-vcloud-authorization", token.to_string())),
+            (None, None) => {
+                return Err(Error::Api {
+                    status: response.status(),
+                    message: "no token in the login response".into(),
+                })
+            }
+        };
+        Ok(())
+    }
+
+    /// Ends the session and forgets its token.
+    pub fn logout(&mut self) -> Result<(), Error> {
+        self.request::<()>("DELETE", "/session", &[], &["application/*+json"])?;
+        self.authorization = None;
+        Ok(())
+    }
+
+    fn accept(&self, media_types: &[&str]) -> String {
+        media_types
+            .iter()
+            .map(|t| format!("{};version={}", t, self.version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn call(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        accept: &[&str],
+        body: Option<(&str, String)>,
+    ) -> Result<String, Error> {
+        let url = if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}{}", self.base_url, path)
+        };
+        let mut request = self
+            .agent
+            .request(method, &url)
+            .set("Accept", &self.accept(accept));
+        for (name, value) in query {
+            request = request.query(name, value);
+        }
+        if let Some((header, value)) = &self.authorization {
+            request = request.set(header, value);
+        }
+        if let Some(tenant) = &self.tenant {
+            request = request.set("X-VMWARE-VCLOUD-TENANT-CONTEXT", tenant);
+        }
+        let response = match body {
+            Some((content_type, body)) => request
+                .set(
+                    "Content-Type",
+                    &format!("{};version={}", content_type, self.version),
+                )
+                .send_string(&body)?,
+            None => request.call()?,
+        };
+        response
+            .into_string()
+            .map_err(|e| Error::Transport(e.to_string()))
+    }
+
+    /// Sends a request without a body. `path` is below the base URL, or an
+    /// `href` the API gave.
+    pub fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        accept: &[&str],
+    ) -> Result<T, Error> {
+        parse(&self.call(method, path, query, accept, None)?)
+    }
+
+    /// Sends `body` as JSON of the media type `content_type`.
+    pub fn send<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(&str, &str)],
+        accept: &[&str],
+        content_type: &str,
+        body: &B,
+    ) -> Result<T, Error> {
+        let body = serde_json::to_string(body).map_err(|e| Error::Json(e.to_string()))?;
+        parse(&self.call(method, path, query, accept, Some((content_type, body)))?)
+    }
+
+    /// Reads every page of a list, following `nextPage` links.
+    pub fn all_pages<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        accept: &[&str],
+    ) -> Result<Vec<T>, Error> {
+        let mut pages = Vec::new();
+        let mut page: Value = self.request("GET", path, query, accept)?;
+        loop {
+            let next = link(&page, "nextPage");
+            pages.push(serde_json::from_value(page).map_err(|e| Error::Json(e.to_string()))?);
+            match next {
+                Some(href) => page = self.request("GET", &href, &[], accept)?,
+                None => return Ok(pages),
+            }
+        }
+    }
+
+    /// Polls the task at `href` until it ends, failing unless it succeeds.
+    pub fn wait_for_task<T: DeserializeOwned>(
+        &self,
+        href: &str,
+        timeout: Duration,
+    ) -> Result<T, Error> {
+        let start = Instant::now();
+        loop {
+            let task: Value = self.request(
+                "GET",
+                href,
+                &[],
+                &["application/vnd.vmware.vcloud.task+json"],
+            )?;
+            let status = task["status"].as_str().unwrap_or_default().to_string();
+            if status == "success" {
+                return serde_json::from_value(task).map_err(|e| Error::Json(e.to_string()));
+            }
+            if TASK_DONE.contains(&status.as_str()) {
+                let message = task["error"]["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                return Err(Error::Task { status, message });
+            }
+            if start.elapsed() >= timeout {
+                return Err(Error::Timeout);
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, Error> {
+    let body = if body.trim().is_empty() { "null" } else { body };
+    serde_json::from_str(body).map_err(|e| Error::Json(e.to_string()))
+}
+
+/// The `href` of the first link with relation `rel`.
+fn link(value: &Value, rel: &str) -> Option<String> {
+    value["link"]
+        .as_array()?
+        .iter()
+        .find(|l| l["rel"] == rel)?
+        .get("href")?
+        .as_str()
+        .map(String::from)
+}
+
+fn version_key(version: &str) -> Vec<u32> {
+    version.split('.').filter_map(|p| p.parse().ok()).collect()
+}
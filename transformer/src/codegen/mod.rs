@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+mod client;
+pub mod rust;
+
+/// The manifest of a generated crate. `extra` is appended to its
+/// dependencies. It is its own workspace so it builds wherever it is written.
+pub(crate) fn cargo_toml(crate_name: &str, description: &str, extra: &str) -> String {
+    format!(
+        r#"[package]
+name = {:?}
+version = "0.1.0"
+edition = "2018"
+description = {:?}
+
+[dependencies]
+serde = {{ version = "1", features = ["derive"] }}
+serde_json = "1"
+{}
+[workspace]
+"#,
+        crate_name, description, extra
+    )
+}
+
+/// Writes generated files below `dir`, creating directories as needed.
+pub fn write(files: &BTreeMap<&str, String>, dir: &Path) -> io::Result<()> {
+    for (path, content) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+/// Writes a generated crate with the example program `source` to a directory
+/// of its own and runs it with the environment variables `env`, fetching the
+/// crate's dependencies.
+#[cfg(test)]
+pub(crate) fn run_example(
+    files: &BTreeMap<&str, String>,
+    example: &str,
+    source: &str,
+    features: &[&str],
+    env: &[(&str, &str)],
+) {
+    let dir = std::env::temp_dir().join(format!(
+        "transformer-{}-{}",
+        example,
+        std::process::id()
+    ));
+    write(files, &dir).unwrap();
+    let path = format!("examples/{}.rs", example);
+    std::fs::create_dir_all(dir.join("examples")).unwrap();
+    std::fs::write(dir.join(&path), source).unwrap();
+    let mut args = vec!["run", "--quiet", "--example", example];
+    if !features.is_empty() {
+        args.push("--features");
+        args.extend(features);
+    }
+    let output = std::process::Command::new(option_env!("CARGO").unwrap_or("cargo"))
+        .args(&args)
+        .current_dir(&dir)
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
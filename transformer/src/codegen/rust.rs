@@ -0,0 +1,902 @@
+//! Serde models for the spec's schemas, as a standalone crate.
+//!
+//! `allOf` inheritance is flattened into structs, discriminated base types
+//! become enums tagged by the discriminator and string enumerations become
+//! Rust enums. Fields that would make a type contain itself are boxed.
+
+use crate::spec::SCHEMA_PREFIX;
+use indexmap::IndexMap;
+use openapiv3::{
+    AdditionalProperties, IntegerFormat, NumberFormat, OpenAPI, ReferenceOr, Schema, SchemaKind,
+    Type, VariantOrUnknownOrEmpty,
+};
+#[cfg(test)]
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// The optional dependencies of the `client` module. `tls` is needed to talk
+/// to a real server.
+const CLIENT_DEPENDENCIES: &str = r#"base64 = { version = "0.22", optional = true }
+ureq = { version = "2", default-features = false, features = ["json"], optional = true }
+
+[features]
+client = ["base64", "ureq"]
+tls = ["client", "ureq/tls"]
+"#;
+
+const KEYWORDS: [&str; 51] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// The Rust type of a field, variant or alias.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RustType {
+    /// A generated type.
+    Named(String),
+    Primitive(&'static str),
+    Vec(Box<RustType>),
+    Map(Box<RustType>),
+    Boxed(Box<RustType>),
+    Value,
+}
+
+impl RustType {
+    pub(crate) fn render(&self) -> String {
+        match self {
+            RustType::Named(name) => name.clone(),
+            RustType::Primitive(p) => p.to_string(),
+            RustType::Vec(t) => format!("Vec<{}>", t.render()),
+            RustType::Map(t) => format!("std::collections::BTreeMap<String, {}>", t.render()),
+            RustType::Boxed(t) => format!("Box<{}>", t.render()),
+            RustType::Value => "serde_json::Value".to_string(),
+        }
+    }
+
+    /// The generated type a value holds inline, which is what can make a
+    /// type infinitely sized.
+    fn inline_name(&self) -> Option<&str> {
+        match self {
+            RustType::Named(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn boxed(&mut self) {
+        *self = RustType::Boxed(Box::new(self.clone()));
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) json_name: String,
+    /// The name quick-xml knows the field by, for types that read XML too:
+    /// `@name` for attributes and `$text` for text content.
+    pub(crate) xml_name: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) r#type: RustType,
+    pub(crate) required: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct Variant {
+    name: String,
+    /// The value this variant is told apart by, if it is tagged.
+    tag: Option<String>,
+    r#type: RustType,
+}
+
+#[derive(Debug)]
+pub(crate) enum Item {
+    Struct {
+        name: String,
+        description: Option<String>,
+        fields: Vec<Field>,
+    },
+    Tagged {
+        name: String,
+        description: Option<String>,
+        tag: String,
+        variants: Vec<Variant>,
+    },
+    Untagged {
+        name: String,
+        description: Option<String>,
+        variants: Vec<Variant>,
+    },
+    StringEnum {
+        name: String,
+        description: Option<String>,
+        values: Vec<String>,
+    },
+    Alias {
+        name: String,
+        description: Option<String>,
+        r#type: RustType,
+    },
+}
+
+impl Item {
+    fn name(&self) -> &str {
+        match self {
+            Item::Struct { name, .. }
+            | Item::Tagged { name, .. }
+            | Item::Untagged { name, .. }
+            | Item::StringEnum { name, .. }
+            | Item::Alias { name, .. } => name,
+        }
+    }
+
+    fn types_mut(&mut self) -> Vec<&mut RustType> {
+        match self {
+            Item::Struct { fields, .. } => fields.iter_mut().map(|f| &mut f.r#type).collect(),
+            Item::Tagged { variants, .. } | Item::Untagged { variants, .. } => {
+                variants.iter_mut().map(|v| &mut v.r#type).collect()
+            }
+            Item::Alias { r#type, .. } => vec![r#type],
+            Item::StringEnum { .. } => Vec::new(),
+        }
+    }
+}
+
+struct Generator<'a> {
+    schemas: &'a IndexMap<String, ReferenceOr<Schema>>,
+    /// The Rust name of each schema; the enum for discriminated base types.
+    names: BTreeMap<&'a str, String>,
+    /// The structs holding the fields of discriminated base types.
+    structs: BTreeMap<&'a str, String>,
+    used: BTreeSet<String>,
+    items: Vec<Item>,
+}
+
+/// The files of a crate with a model for every schema in the spec, by path.
+pub fn generate(spec: &OpenAPI, crate_name: &str) -> BTreeMap<&'static str, String> {
+    let empty = IndexMap::new();
+    let schemas = spec
+        .components
+        .as_ref()
+        .map_or(&empty, |components| &components.schemas);
+    let mut generator = Generator::new(schemas);
+    for (name, schema) in schemas {
+        generator.schema(name, schema);
+    }
+    let client = super::client::client_rs(spec, |r| generator.reference_type(r));
+    let title = format!("{} {}", spec.info.title, spec.info.version);
+    let mut lib = lib_rs(&title, &mut generator.items);
+    let api_version = spec.info.version.split_whitespace().last().unwrap_or_default();
+    write!(
+        lib,
+        "\n/// The API version the models describe.\npub const API_VERSION: &str = {:?};\n\n#[cfg(feature = \"client\")]\npub mod client;\n",
+        api_version
+    )
+    .unwrap();
+    let cargo_toml = super::cargo_toml(
+        crate_name,
+        &format!("Serde models for {}", title),
+        CLIENT_DEPENDENCIES,
+    );
+    let mut files = BTreeMap::new();
+    files.insert("Cargo.toml", cargo_toml);
+    files.insert("src/client.rs", client);
+    files.insert("src/lib.rs", lib);
+    files
+}
+
+/// The source of a `lib.rs` declaring `items`, boxing the fields that need it.
+pub(crate) fn lib_rs(title: &str, items: &mut [Item]) -> String {
+    box_recursive_types(items);
+    let mut lib = String::new();
+    writeln!(lib, "//! Serde models for {}.", title).unwrap();
+    lib.push_str("//!\n//! Generated by `transformer codegen`. Do not edit.\n\n");
+    lib.push_str("#![allow(clippy::large_enum_variant, clippy::enum_variant_names)]\n\n");
+    lib.push_str("use serde::{Deserialize, Serialize};\n");
+    for item in items.iter() {
+        lib.push('\n');
+        render(&mut lib, item);
+    }
+    lib
+}
+
+impl<'a> Generator<'a> {
+    fn new(schemas: &'a IndexMap<String, ReferenceOr<Schema>>) -> Self {
+        let mut generator = Generator {
+            schemas,
+            names: BTreeMap::new(),
+            structs: BTreeMap::new(),
+            used: BTreeSet::new(),
+            items: Vec::new(),
+        };
+        for name in schemas.keys() {
+            let rust_name = generator.unique(pascal_case(name));
+            generator.names.insert(name, rust_name);
+        }
+        for (name, schema) in schemas {
+            if let ReferenceOr::Item(schema) = schema {
+                if is_discriminated_object(schema) {
+                    let rust_name = generator.unique(format!("{}Struct", generator.names[&**name]));
+                    generator.structs.insert(name, rust_name);
+                }
+            }
+        }
+        generator
+    }
+
+    fn unique(&mut self, name: String) -> String {
+        let name = (1..)
+            .map(|i| match i {
+                1 => name.clone(),
+                i => format!("{}{}", name, i),
+            })
+            .find(|n| !self.used.contains(n))
+            .unwrap();
+        self.used.insert(name.clone());
+        name
+    }
+
+    fn resolve(&self, reference: &str) -> Option<(&'a str, &'a Schema)> {
+        let name = reference.strip_prefix(SCHEMA_PREFIX)?;
+        match self.schemas.get_full(name)? {
+            (_, name, ReferenceOr::Item(schema)) => Some((name, schema)),
+            (_, _, ReferenceOr::Reference { reference }) => self.resolve(reference),
+        }
+    }
+
+    fn reference_type(&self, reference: &str) -> RustType {
+        match reference
+            .strip_prefix(SCHEMA_PREFIX)
+            .and_then(|name| self.names.get(name))
+        {
+            Some(name) => RustType::Named(name.clone()),
+            None => {
+                warn!("Unknown schema {}", reference);
+                RustType::Value
+            }
+        }
+    }
+
+    /// The properties of an object schema including inherited ones, with
+    /// whether they are required. Discriminator properties are left out as
+    /// the enums of the discriminated types carry them.
+    fn properties(
+        &self,
+        schema: &'a Schema,
+        properties: &mut IndexMap<&'a str, (&'a ReferenceOr<Box<Schema>>, bool)>,
+        discriminators: &mut BTreeSet<&'a str>,
+        depth: usize,
+    ) {
+        if depth > 32 {
+            return;
+        }
+        if let Some(discriminator) = &schema.schema_data.discriminator {
+            discriminators.insert(&discriminator.property_name);
+        }
+        let (own, required) = match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(o)) => (&o.properties, &o.required),
+            SchemaKind::Any(a) => (&a.properties, &a.required),
+            SchemaKind::AllOf { all_of } => {
+                for member in all_of {
+                    match member {
+                        ReferenceOr::Reference { reference } => {
+                            if let Some((_, parent)) = self.resolve(reference) {
+                                self.properties(parent, properties, discriminators, depth + 1);
+                            }
+                        }
+                        ReferenceOr::Item(member) => {
+                            self.properties(member, properties, discriminators, depth + 1)
+                        }
+                    }
+                }
+                return;
+            }
+            _ => return,
+        };
+        for (name, property) in own {
+            properties.insert(name, (property, required.contains(name)));
+        }
+    }
+
+    fn struct_item(&mut self, name: String, schema: &'a Schema) -> Item {
+        let mut properties = IndexMap::new();
+        let mut discriminators = BTreeSet::new();
+        self.properties(schema, &mut properties, &mut discriminators, 0);
+        let mut idents = BTreeSet::new();
+        let fields = properties
+            .into_iter()
+            .filter(|(json_name, _)| !discriminators.contains(json_name))
+            .map(|(json_name, (property, required))| {
+                let ident = snake_case(json_name);
+                let ident = (1..)
+                    .map(|i| match i {
+                        1 => ident.clone(),
+                        i => format!("{}_{}", ident.trim_start_matches("r#"), i),
+                    })
+                    .find(|i| idents.insert(i.clone()))
+                    .unwrap();
+                Field {
+                    name: ident,
+                    json_name: json_name.to_string(),
+                    xml_name: None,
+                    description: match property {
+                        ReferenceOr::Item(p) => p.schema_data.description.clone(),
+                        ReferenceOr::Reference { .. } => None,
+                    },
+                    r#type: self.property_type(&name, json_name, property),
+                    required,
+                }
+            })
+            .collect();
+        Item::Struct {
+            name,
+            description: schema.schema_data.description.clone(),
+            fields,
+        }
+    }
+
+    fn property_type(
+        &mut self,
+        owner: &str,
+        property: &str,
+        schema: &'a ReferenceOr<Box<Schema>>,
+    ) -> RustType {
+        match schema {
+            ReferenceOr::Reference { reference } => self.reference_type(reference),
+            ReferenceOr::Item(schema) => self.inline_type(owner, property, schema),
+        }
+    }
+
+    /// The type of a schema nested in the schema of `owner`, generating the
+    /// enums and structs it needs.
+    fn inline_type(&mut self, owner: &str, property: &str, schema: &'a Schema) -> RustType {
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::String(s)) => {
+                let values = s.enumeration.iter().flatten().cloned().collect::<Vec<_>>();
+                if values.is_empty() {
+                    RustType::Primitive("String")
+                } else {
+                    let name = self.unique(format!("{}{}", owner, pascal_case(property)));
+                    self.items.push(Item::StringEnum {
+                        name: name.clone(),
+                        description: schema.schema_data.description.clone(),
+                        values,
+                    });
+                    RustType::Named(name)
+                }
+            }
+            SchemaKind::Type(Type::Integer(i)) => match i.format {
+                VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => RustType::Primitive("i32"),
+                _ => RustType::Primitive("i64"),
+            },
+            SchemaKind::Type(Type::Number(n)) => match n.format {
+                VariantOrUnknownOrEmpty::Item(NumberFormat::Float) => RustType::Primitive("f32"),
+                _ => RustType::Primitive("f64"),
+            },
+            SchemaKind::Type(Type::Boolean {}) => RustType::Primitive("bool"),
+            SchemaKind::Type(Type::Array(a)) => RustType::Vec(Box::new(match &a.items {
+                Some(items) => self.property_type(owner, property, items),
+                None => RustType::Value,
+            })),
+            SchemaKind::Type(Type::Object(o)) if o.properties.is_empty() => {
+                match &o.additional_properties {
+                    Some(AdditionalProperties::Schema(s)) => RustType::Map(Box::new(match &**s {
+                        ReferenceOr::Reference { reference } => self.reference_type(reference),
+                        ReferenceOr::Item(s) => self.inline_type(owner, property, s),
+                    })),
+                    _ => RustType::Value,
+                }
+            }
+            SchemaKind::AllOf { all_of } if all_of.len() == 1 => match &all_of[0] {
+                ReferenceOr::Reference { reference } => self.reference_type(reference),
+                ReferenceOr::Item(s) => self.inline_type(owner, property, s),
+            },
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. } => {
+                let name = self.unique(format!("{}{}", owner, pascal_case(property)));
+                let item = self.struct_item(name.clone(), schema);
+                self.items.push(item);
+                RustType::Named(name)
+            }
+            _ => RustType::Value,
+        }
+    }
+
+    fn variants(&mut self, owner: &str, members: &'a [ReferenceOr<Schema>]) -> Vec<Variant> {
+        let mut names = BTreeSet::new();
+        members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| {
+                let r#type = match member {
+                    ReferenceOr::Reference { reference } => self.reference_type(reference),
+                    ReferenceOr::Item(s) => {
+                        self.inline_type(owner, &format!("variant{}", i + 1), s)
+                    }
+                };
+                let name = match &r#type {
+                    RustType::Named(name) => name.clone(),
+                    _ => format!("Variant{}", i + 1),
+                };
+                Variant {
+                    name: unique_in(&mut names, name),
+                    tag: None,
+                    r#type,
+                }
+            })
+            .collect()
+    }
+
+    /// The variants of a type discriminated by `mapping`, each holding the
+    /// fields of its type.
+    fn tagged_variants(&self, mapping: &'a IndexMap<String, String>) -> Vec<Variant> {
+        let mut names = std::iter::once("Unknown".to_string()).collect();
+        mapping
+            .iter()
+            .map(|(tag, reference)| {
+                let r#type = match reference
+                    .strip_prefix(SCHEMA_PREFIX)
+                    .and_then(|name| self.structs.get(name))
+                {
+                    Some(name) => RustType::Named(name.clone()),
+                    None => self.reference_type(reference),
+                };
+                Variant {
+                    name: unique_in(&mut names, pascal_case(tag)),
+                    tag: Some(tag.clone()),
+                    r#type,
+                }
+            })
+            .collect()
+    }
+
+    fn schema(&mut self, schema_name: &'a str, schema: &'a ReferenceOr<Schema>) {
+        let name = self.names[schema_name].clone();
+        let schema = match schema {
+            ReferenceOr::Reference { reference } => {
+                let r#type = self.reference_type(reference);
+                self.items.push(Item::Alias {
+                    name,
+                    description: None,
+                    r#type,
+                });
+                return;
+            }
+            ReferenceOr::Item(schema) => schema,
+        };
+        let description = schema.schema_data.description.clone();
+        let discriminator = schema
+            .schema_data
+            .discriminator
+            .as_ref()
+            .filter(|d| !d.mapping.is_empty());
+        let item = match (&schema.schema_kind, discriminator) {
+            (SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. }, Some(discriminator))
+                if self.structs.contains_key(schema_name) =>
+            {
+                let struct_name = self.structs[schema_name].clone();
+                let fields = self.struct_item(struct_name, schema);
+                self.items.push(fields);
+                Item::Tagged {
+                    name,
+                    description,
+                    tag: discriminator.property_name.clone(),
+                    variants: self.tagged_variants(&discriminator.mapping),
+                }
+            }
+            (SchemaKind::OneOf { .. }, Some(discriminator)) => Item::Tagged {
+                name,
+                description,
+                tag: discriminator.property_name.clone(),
+                variants: self.tagged_variants(&discriminator.mapping),
+            },
+            (SchemaKind::OneOf { one_of: members }, None)
+            | (SchemaKind::AnyOf { any_of: members }, _) => Item::Untagged {
+                variants: self.variants(&name, members),
+                name,
+                description,
+            },
+            (SchemaKind::Type(Type::String(s)), _)
+                if s.enumeration.iter().flatten().count() > 0 =>
+            {
+                Item::StringEnum {
+                    name,
+                    description,
+                    values: s.enumeration.iter().flatten().cloned().collect(),
+                }
+            }
+            (SchemaKind::AllOf { all_of }, _) if all_of.len() > 1 => self.struct_item(name, schema),
+            (SchemaKind::Type(Type::Object(o)), _) if !o.properties.is_empty() => {
+                self.struct_item(name, schema)
+            }
+            (SchemaKind::Any(a), _) if !a.properties.is_empty() => self.struct_item(name, schema),
+            _ => Item::Alias {
+                r#type: self.inline_type(&name, "item", schema),
+                name,
+                description,
+            },
+        };
+        self.items.push(item);
+    }
+}
+
+/// Boxes the fields and variants through which a type would contain itself.
+fn box_recursive_types(items: &mut [Item]) {
+    let mut dependencies = BTreeMap::<String, Vec<String>>::new();
+    for item in items.iter_mut() {
+        let inline = item
+            .types_mut()
+            .into_iter()
+            .filter_map(|t| t.inline_name().map(String::from))
+            .collect();
+        dependencies.insert(item.name().to_string(), inline);
+    }
+    let reaches = |from: &str, to: &str| {
+        let mut stack = vec![from.to_string()];
+        let mut seen = BTreeSet::new();
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if seen.insert(name.clone()) {
+                stack.extend(dependencies.get(&name).into_iter().flatten().cloned());
+            }
+        }
+        false
+    };
+    let mut boxed = Vec::new();
+    for item in items.iter_mut() {
+        let name = item.name().to_string();
+        for (i, t) in item.types_mut().into_iter().enumerate() {
+            if t.inline_name().is_some_and(|n| reaches(n, &name)) {
+                boxed.push((name.clone(), i));
+            }
+        }
+    }
+    for item in items.iter_mut() {
+        let name = item.name().to_string();
+        for (i, t) in item.types_mut().into_iter().enumerate() {
+            if boxed.contains(&(name.clone(), i)) {
+                t.boxed();
+            }
+        }
+    }
+}
+
+fn is_discriminated_object(schema: &Schema) -> bool {
+    matches!(
+        &schema.schema_kind,
+        SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. }
+    ) && schema
+        .schema_data
+        .discriminator
+        .iter()
+        .any(|d| !d.mapping.is_empty())
+}
+
+pub(crate) fn unique_in(names: &mut BTreeSet<String>, name: String) -> String {
+    let name = (1..)
+        .map(|i| match i {
+            1 => name.clone(),
+            i => format!("{}{}", name, i),
+        })
+        .find(|n| !names.contains(n))
+        .unwrap();
+    names.insert(name.clone());
+    name
+}
+
+/// A type or variant name: `vcloud-ext_VimServerType` becomes `VcloudExtVimServerType`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    let mut pascal = String::new();
+    for part in name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|p| !p.is_empty())
+    {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            pascal.push(first.to_ascii_uppercase());
+            pascal.push_str(chars.as_str());
+        }
+    }
+    if pascal.is_empty() {
+        pascal.push_str("Empty");
+    }
+    if pascal.starts_with(|c: char| c.is_ascii_digit()) {
+        pascal.insert(0, 'V');
+    }
+    if KEYWORDS.contains(&pascal.as_str()) {
+        pascal.push('_');
+    }
+    pascal
+}
+
+/// A field name: `numberOfVMs` becomes `number_of_vms`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let previous = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let word_start = match previous {
+                Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_ascii_uppercase() => {
+                    matches!(next, Some(n) if n.is_ascii_lowercase() && *n != 's')
+                }
+                _ => false,
+            };
+            if word_start {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            snake.push(c);
+        } else {
+            snake.push('_');
+        }
+    }
+    let mut snake = snake
+        .split('_')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if snake.is_empty() {
+        snake.push_str("field");
+    }
+    if snake.starts_with(|c: char| c.is_ascii_digit()) {
+        snake.insert_str(0, "n_");
+    }
+    match snake.as_str() {
+        "self" | "super" | "crate" => snake + "_",
+        s if KEYWORDS.contains(&s) => format!("r#{}", snake),
+        _ => snake,
+    }
+}
+
+pub(crate) fn render_doc(out: &mut String, indent: &str, description: &Option<String>) {
+    for line in description.iter().flat_map(|d| d.lines()) {
+        // Keep descriptions from being read as doc tests.
+        let line = line.trim().replace("```", "'''");
+        if line.is_empty() {
+            writeln!(out, "{}///", indent).unwrap();
+        } else {
+            writeln!(out, "{}/// {}", indent, line).unwrap();
+        }
+    }
+}
+
+fn render_rename(out: &mut String, indent: &str, ident: &str, json_name: &str) {
+    if ident.trim_start_matches("r#") != json_name {
+        writeln!(out, "{}#[serde(rename = {:?})]", indent, json_name).unwrap();
+    }
+}
+
+const DERIVES: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+
+fn render(out: &mut String, item: &Item) {
+    match item {
+        Item::Struct {
+            name,
+            description,
+            fields,
+        } => {
+            render_doc(out, "", description);
+            writeln!(out, "{}\npub struct {} {{", DERIVES, name).unwrap();
+            for field in fields {
+                render_doc(out, "    ", &field.description);
+                match &field.xml_name {
+                    Some(xml_name) => {
+                        writeln!(
+                            out,
+                            "    #[serde(rename(deserialize = {:?}), alias = {:?})]",
+                            field.json_name, xml_name
+                        )
+                        .unwrap();
+                        writeln!(
+                            out,
+                            "    #[cfg_attr(feature = \"xml\", serde(rename(serialize = {:?})))]",
+                            xml_name
+                        )
+                        .unwrap();
+                        writeln!(
+                            out,
+                            "    #[cfg_attr(not(feature = \"xml\"), serde(rename(serialize = {:?})))]",
+                            field.json_name
+                        )
+                        .unwrap();
+                    }
+                    None => render_rename(out, "    ", &field.name, &field.json_name),
+                }
+                let r#type = match (&field.r#type, field.required) {
+                    (_, true) => field.r#type.render(),
+                    (RustType::Vec(_), false) => {
+                        out.push_str(
+                            "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n",
+                        );
+                        field.r#type.render()
+                    }
+                    (RustType::Map(_), false) => {
+                        out.push_str("    #[serde(default, skip_serializing_if = \"std::collections::BTreeMap::is_empty\")]\n");
+                        field.r#type.render()
+                    }
+                    (_, false) => {
+                        out.push_str(
+                            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n",
+                        );
+                        format!("Option<{}>", field.r#type.render())
+                    }
+                };
+                writeln!(out, "    pub {}: {},", field.name, r#type).unwrap();
+            }
+            out.push_str("}\n");
+        }
+        Item::Tagged {
+            name,
+            description,
+            tag,
+            variants,
+        } => {
+            render_doc(out, "", description);
+            writeln!(
+                out,
+                "{}\n#[serde(tag = {:?})]\npub enum {} {{",
+                DERIVES, tag, name
+            )
+            .unwrap();
+            for variant in variants {
+                if let Some(tag) = &variant.tag {
+                    render_rename(out, "    ", &variant.name, tag);
+                }
+                writeln!(out, "    {}({}),", variant.name, variant.r#type.render()).unwrap();
+            }
+            out.push_str(
+                "    /// A type this model doesn't know.\n    #[serde(other)]\n    Unknown,\n}\n",
+            );
+        }
+        Item::Untagged {
+            name,
+            description,
+            variants,
+        } => {
+            render_doc(out, "", description);
+            writeln!(out, "{}\n#[serde(untagged)]\npub enum {} {{", DERIVES, name).unwrap();
+            for variant in variants {
+                writeln!(out, "    {}({}),", variant.name, variant.r#type.render()).unwrap();
+            }
+            out.push_str("}\n");
+        }
+        Item::StringEnum {
+            name,
+            description,
+            values,
+        } => {
+            render_doc(out, "", description);
+            writeln!(
+                out,
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{",
+                name
+            )
+            .unwrap();
+            let mut names = BTreeSet::new();
+            for value in values {
+                let variant = unique_in(&mut names, pascal_case(value));
+                render_rename(out, "    ", &variant, value);
+                writeln!(out, "    {},", variant).unwrap();
+            }
+            out.push_str("}\n");
+        }
+        Item::Alias {
+            name,
+            description,
+            r#type,
+        } => {
+            render_doc(out, "", description);
+            writeln!(out, "pub type {} = {};", name, r#type.render()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) fn test_spec() -> OpenAPI {
+    serde_json::from_str(include_str!("test_spec.json")).unwrap()
+}
+
+#[test]
+fn names_test() {
+    assert_eq!(
+        pascal_case("vcloud-ext_VimServerType"),
+        "VcloudExtVimServerType"
+    );
+    assert_eq!(
+        pascal_case("query-parameter_format"),
+        "QueryParameterFormat"
+    );
+    assert_eq!(pascal_case("1.0"), "V10");
+    assert_eq!(pascal_case("self"), "Self_");
+    assert_eq!(snake_case("numberOfVMs"), "number_of_vms");
+    assert_eq!(snake_case("VAppScopedLocalId"), "v_app_scoped_local_id");
+    assert_eq!(snake_case("memoryMB"), "memory_mb");
+    assert_eq!(snake_case("type"), "r#type");
+    assert_eq!(snake_case("_type"), "r#type");
+    assert_eq!(snake_case("self"), "self_");
+    assert_eq!(snake_case("{http://x}y"), "http_x_y");
+}
+
+#[test]
+fn generate_test() {
+    let files = generate(&test_spec(), "test-models");
+    let lib = &files["src/lib.rs"];
+    // Inheritance is flattened and the discriminator left to the enum.
+    assert!(lib.contains(
+        "pub struct VcloudQueryResultVMRecordType {
+    #[serde(default, skip_serializing_if = \"Option::is_none\")]
+    pub href: Option<String>,
+    /// Memory in MB.
+    #[serde(rename = \"memoryMB\")]
+    #[serde(default, skip_serializing_if = \"Option::is_none\")]
+    pub memory_mb: Option<i64>,
+}"
+    ));
+    assert!(lib.contains(
+        "#[serde(tag = \"_type\")]
+pub enum VcloudQueryResultRecordType {
+    QueryResultVMRecordType(VcloudQueryResultVMRecordType),
+    QueryResultRecordType(VcloudQueryResultRecordTypeStruct),
+    /// A type this model doesn't know.
+    #[serde(other)]
+    Unknown,
+}"
+    ));
+    assert!(lib.contains("pub record: Vec<VcloudQueryResultRecordType>,"));
+    // Recursive types are boxed.
+    assert!(lib.contains("pub inner_error: Option<Box<VcloudExtErrorType>>,"));
+    assert!(lib.contains(
+        "pub enum VcloudExtErrorTypeSeverity {
+    #[serde(rename = \"warning\")]
+    Warning,
+    #[serde(rename = \"error\")]
+    Error,
+}"
+    ));
+    assert!(lib.contains("pub r#type: Option<String>,"));
+    assert!(lib.contains("pub other_attributes: std::collections::BTreeMap<String, String>,"));
+    assert!(lib.contains("pub type QueryParameterPage = i32;"));
+    assert!(lib.contains(
+        "#[serde(tag = \"name\")]
+pub enum QueryResultRecords {
+    #[serde(rename = \"vm\")]
+    Vm(VcloudQueryResultRecordsType),"
+    ));
+    assert_eq!(
+        files["Cargo.toml"].lines().take(2).collect::<Vec<_>>(),
+        vec!["[package]", "name = \"test-models\""]
+    );
+}
+
+#[test]
+#[ignore = "builds a generated crate, fetching its dependencies"]
+fn generated_crate_compiles_test() {
+    let files = generate(&test_spec(), "test-models");
+    // A program round-tripping a polymorphic record through the models.
+    let source = format!(
+        "fn main() {{
+    let json = {:?};
+    let records: test_models::VcloudQueryResultRecordsType = serde_json::from_str(json).unwrap();
+    match &records.record[0] {{
+        test_models::VcloudQueryResultRecordType::QueryResultVMRecordType(vm) => {{
+            assert_eq!(vm.memory_mb, Some(1024))
+        }}
+        other => panic!(\"unexpected record {{:?}}\", other),
+    }}
+    assert_eq!(serde_json::to_value(&records).unwrap(), serde_json::from_str::<serde_json::Value>(json).unwrap());
+}}",
+        json!({
+            "name": "vm",
+            "record": [{"_type": "QueryResultVMRecordType", "href": "https://example.com/api/vm/1", "memoryMB": 1024}]
+        })
+        .to_string()
+    );
+    super::run_example(&files, "round_trip", &source, &[], &[]);
+}
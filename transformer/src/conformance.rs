@@ -0,0 +1,593 @@
+//! Checks traffic captured in a HAR file against a spec.
+//!
+//! Each entry below `/api` is matched to a path template and method. Its
+//! status, the content types and versions of its request and response, and
+//! its JSON bodies are checked against the operation, and the report
+//! aggregates what disagrees.
+
+use crate::info::api_version;
+use crate::spec::{bare_media_type, match_template, operations};
+use crate::validate::{Validator, ViolationKind};
+use indexmap::IndexMap;
+use openapiv3::{MediaType, OpenAPI, Operation, ReferenceOr, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const RESPONSE_PREFIX: &str = "#/components/responses/";
+
+/// A HAR file, with what the check needs of it.
+#[derive(Debug, Deserialize)]
+pub struct Har {
+    pub log: Log,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Log {
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Entry {
+    pub request: HarRequest,
+    pub response: HarResponse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<Header>,
+    #[serde(rename = "postData")]
+    pub post_data: Option<PostData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostData {
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HarResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<Header>,
+    pub content: Content,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Content {
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: String,
+    pub text: Option<String>,
+    pub encoding: Option<String>,
+}
+
+pub(crate) fn header<'a>(headers: &'a [Header], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn version(media_type: &str) -> Option<&str> {
+    media_type
+        .split(';')
+        .find_map(|p| p.trim().strip_prefix("version="))
+}
+
+/// The path below `/api` of a URL, without its query.
+pub(crate) fn api_path(url: &str) -> Option<&str> {
+    let path = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest.find('/').map_or("/", |i| &rest[i..]));
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    match path.strip_prefix("/api") {
+        Some(path) if path.is_empty() || path.starts_with('/') => Some(path),
+        _ => None,
+    }
+}
+
+/// What disagrees with the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Problem {
+    /// The path matches no template.
+    UndocumentedEndpoint,
+    /// The path has no operation for the method.
+    UndocumentedMethod,
+    UnexpectedStatus,
+    UnexpectedContentType,
+    /// A media type is of another API version than the spec's.
+    VersionMismatch,
+    /// A request body disagrees with its schema.
+    RequestBody(ViolationKind),
+    /// A response body disagrees with its schema.
+    ResponseBody(ViolationKind),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::UndocumentedEndpoint => write!(f, "undocumented endpoint"),
+            Problem::UndocumentedMethod => write!(f, "undocumented method"),
+            Problem::UnexpectedStatus => write!(f, "unexpected status"),
+            Problem::UnexpectedContentType => write!(f, "unexpected content type"),
+            Problem::VersionMismatch => write!(f, "version mismatch"),
+            Problem::RequestBody(kind) => write!(f, "request body: {}", violation_kind(*kind)),
+            Problem::ResponseBody(kind) => write!(f, "response body: {}", violation_kind(*kind)),
+        }
+    }
+}
+
+fn violation_kind(kind: ViolationKind) -> &'static str {
+    match kind {
+        ViolationKind::MissingSchema => "no schema",
+        ViolationKind::Type => "wrong type",
+        ViolationKind::Enumeration => "not in enumeration",
+        ViolationKind::Pattern => "pattern mismatch",
+        ViolationKind::Range => "out of range",
+        ViolationKind::Discriminator => "unknown _type",
+        ViolationKind::Required => "missing required property",
+        ViolationKind::Unexpected => "unexpected property",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The index of the entry in the HAR file.
+    pub entry: usize,
+    pub method: String,
+    /// The path below `/api`.
+    pub path: String,
+    pub problem: Problem,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub entries: usize,
+    /// Entries not below `/api`, such as those of the cloudapi.
+    pub ignored: usize,
+    /// How often each documented operation, such as `GET /vApp/{id}`, was seen.
+    pub operations: BTreeMap<String, usize>,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// How many findings there are of each problem.
+    pub fn counts(&self) -> BTreeMap<Problem, usize> {
+        let mut counts = BTreeMap::new();
+        for finding in &self.findings {
+            *counts.entry(finding.problem).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// How often each undocumented endpoint was called, with segments
+    /// containing digits, such as ids, replaced by `{id}`.
+    pub fn undocumented(&self) -> BTreeMap<String, usize> {
+        let mut undocumented = BTreeMap::new();
+        for finding in &self.findings {
+            if let Problem::UndocumentedEndpoint | Problem::UndocumentedMethod = finding.problem {
+                let path = finding
+                    .path
+                    .split('/')
+                    .map(|s| {
+                        if s.contains(|c: char| c.is_ascii_digit()) {
+                            "{id}"
+                        } else {
+                            s
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                *undocumented
+                    .entry(format!("{} {}", finding.method, path))
+                    .or_insert(0) += 1;
+            }
+        }
+        undocumented
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(
+                f,
+                "#{} {} {}: {}: {}",
+                finding.entry, finding.method, finding.path, finding.problem, finding.message
+            )?;
+        }
+        writeln!(
+            f,
+            "\n{} entries, {} not below /api, {} operations seen",
+            self.entries,
+            self.ignored,
+            self.operations.len()
+        )?;
+        for (problem, count) in self.counts() {
+            writeln!(f, "{:6} {}", count, problem)?;
+        }
+        let undocumented = self.undocumented();
+        if !undocumented.is_empty() {
+            writeln!(f, "\nUndocumented endpoints:")?;
+            for (endpoint, count) in undocumented {
+                writeln!(f, "{:6} {}", count, endpoint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks each entry of a HAR file against the spec.
+pub fn check(spec: &OpenAPI, har: &Har) -> Report {
+    let validator = Validator::new(spec);
+    let mut report = Report {
+        entries: har.log.entries.len(),
+        ..Default::default()
+    };
+    for (index, entry) in har.log.entries.iter().enumerate() {
+        let path = match api_path(&entry.request.url) {
+            Some(path) => path,
+            None => {
+                report.ignored += 1;
+                continue;
+            }
+        };
+        let mut checker = Checker {
+            spec,
+            validator: &validator,
+            entry,
+            index,
+            path,
+            findings: &mut report.findings,
+        };
+        if let Some(operation) = checker.check() {
+            *report.operations.entry(operation).or_insert(0) += 1;
+        }
+    }
+    report
+}
+
+/// Checks one entry.
+struct Checker<'a> {
+    spec: &'a OpenAPI,
+    validator: &'a Validator<'a>,
+    entry: &'a Entry,
+    index: usize,
+    path: &'a str,
+    findings: &'a mut Vec<Finding>,
+}
+
+impl<'a> Checker<'a> {
+    fn find(&mut self, problem: Problem, message: String) {
+        self.findings.push(Finding {
+            entry: self.index,
+            method: self.entry.request.method.to_ascii_uppercase(),
+            path: self.path.to_string(),
+            problem,
+            message,
+        });
+    }
+
+    /// Checks the entry, returning the operation it is of.
+    fn check(&mut self) -> Option<String> {
+        let template = match match_template(self.path, &self.spec.paths) {
+            Some(template) => template,
+            None => {
+                self.find(
+                    Problem::UndocumentedEndpoint,
+                    "no path template matches".into(),
+                );
+                return None;
+            }
+        };
+        let operation = self
+            .spec
+            .paths
+            .paths
+            .get(template)
+            .and_then(ReferenceOr::as_item)
+            .and_then(|item| {
+                operations(item)
+                    .into_iter()
+                    .find_map(|(method, operation)| {
+                        if method.eq_ignore_ascii_case(&self.entry.request.method) {
+                            operation
+                        } else {
+                            None
+                        }
+                    })
+            });
+        let operation = match operation {
+            Some(operation) => operation,
+            None => {
+                self.find(
+                    Problem::UndocumentedMethod,
+                    format!(
+                        "{} has no {} operation",
+                        template, self.entry.request.method
+                    ),
+                );
+                return None;
+            }
+        };
+        self.check_request(operation);
+        self.check_response(operation);
+        Some(format!(
+            "{} {}",
+            self.entry.request.method.to_ascii_uppercase(),
+            template
+        ))
+    }
+
+    fn check_version(&mut self, what: &str, media_type: &str) {
+        let expected = api_version(&self.spec.info);
+        if let Some(version) = version(media_type) {
+            if version != expected {
+                self.find(
+                    Problem::VersionMismatch,
+                    format!("{} has version {}, not {}", what, version, expected),
+                );
+            }
+        }
+    }
+
+    fn check_request(&mut self, operation: &'a Operation) {
+        let request = &self.entry.request;
+        if let Some(accept) = header(&request.headers, "accept") {
+            self.check_version("Accept", accept);
+        }
+        let post_data = match &request.post_data {
+            Some(post_data) if !post_data.text.is_empty() => post_data,
+            _ => return,
+        };
+        let content_type = header(&request.headers, "content-type")
+            .unwrap_or(&post_data.mime_type)
+            .to_string();
+        self.check_version("the request's Content-Type", &content_type);
+        let content = operation
+            .request_body
+            .as_ref()
+            .and_then(ReferenceOr::as_item)
+            .map(|body| &body.content);
+        if let Some(documented) = self.documented(content, &content_type, "request") {
+            self.check_body(documented, &content_type, &post_data.text, Problem::RequestBody);
+        }
+    }
+
+    fn check_response(&mut self, operation: &'a Operation) {
+        let response = &self.entry.response;
+        let documented = match self.response(operation, response.status) {
+            Some(documented) => documented,
+            None => {
+                return self.find(
+                    Problem::UnexpectedStatus,
+                    format!("{} isn't documented", response.status),
+                )
+            }
+        };
+        let text = match &response.content.text {
+            Some(text) if !text.is_empty() => text,
+            _ => return,
+        };
+        let content_type = header(&response.headers, "content-type")
+            .unwrap_or(&response.content.mime_type)
+            .to_string();
+        self.check_version("the response's Content-Type", &content_type);
+        let documented = match self.documented(Some(&documented.content), &content_type, "response")
+        {
+            Some(documented) => documented,
+            None => return,
+        };
+        if response.content.encoding.is_none() {
+            self.check_body(documented, &content_type, text, Problem::ResponseBody);
+        }
+    }
+
+    /// The documented response for a status: its own, its range's or the
+    /// default.
+    fn response(&self, operation: &'a Operation, status: u16) -> Option<&'a Response> {
+        let responses = &operation.responses;
+        let response = responses
+            .responses
+            .iter()
+            .find(|(s, _)| **s == StatusCode::Code(status))
+            .or_else(|| {
+                responses
+                    .responses
+                    .iter()
+                    .find(|(s, _)| **s == StatusCode::Range(status / 100))
+            })
+            .map(|(_, response)| response)
+            .or(responses.default.as_ref())?;
+        match response {
+            ReferenceOr::Item(response) => Some(response),
+            ReferenceOr::Reference { reference } => {
+                match self
+                    .spec
+                    .components
+                    .as_ref()?
+                    .responses
+                    .get(reference.strip_prefix(RESPONSE_PREFIX)?)?
+                {
+                    ReferenceOr::Item(response) => Some(response),
+                    ReferenceOr::Reference { .. } => None,
+                }
+            }
+        }
+    }
+
+    /// How the content documents the media type, noting it if it doesn't.
+    fn documented(
+        &mut self,
+        content: Option<&'a IndexMap<String, MediaType>>,
+        media_type: &str,
+        what: &str,
+    ) -> Option<&'a MediaType> {
+        let bare = bare_media_type(media_type);
+        let documented = content.and_then(|content| {
+            content
+                .iter()
+                .find(|(t, _)| bare_media_type(t).eq_ignore_ascii_case(bare))
+                .map(|(_, documented)| documented)
+        });
+        if documented.is_none() {
+            self.find(
+                Problem::UnexpectedContentType,
+                format!("the {} content type {} isn't documented", what, bare),
+            );
+        }
+        documented
+    }
+
+    /// Checks a body against the schema documented for its media type.
+    fn check_body(
+        &mut self,
+        documented: &'a MediaType,
+        media_type: &str,
+        text: &str,
+        problem: fn(ViolationKind) -> Problem,
+    ) {
+        let schema = match &documented.schema {
+            Some(schema) if bare_media_type(media_type).ends_with("json") => schema,
+            _ => return,
+        };
+        let body: Value = match serde_json::from_str(text) {
+            Ok(body) => body,
+            Err(e) => {
+                return self.find(problem(ViolationKind::Type), format!("invalid JSON: {}", e))
+            }
+        };
+        for violation in self.validator.validate_schema(schema, &body) {
+            self.find(problem(violation.kind), violation.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+use serde_json::json;
+
+#[cfg(test)]
+pub(crate) fn entry(
+    method: &str,
+    url: &str,
+    status: u16,
+    content_type: &str,
+    body: Value,
+) -> Value {
+    json!({
+        "request": {"method": method, "url": url, "headers": [
+            {"name": "Accept", "value": "application/*+json;version=36.0"}
+        ]},
+        "response": {
+            "status": status,
+            "headers": [{"name": "Content-Type", "value": content_type}],
+            "content": {"mimeType": content_type, "text": body.to_string()}
+        }
+    })
+}
+
+#[test]
+fn check_test() {
+    let spec: OpenAPI = serde_json::from_str(include_str!("codegen/test_spec.json")).unwrap();
+    let vapp = "application/vnd.vmware.vcloud.vApp+json;version=36.0";
+    let mut update = entry(
+        "PUT",
+        "https://vcd.example.com/api/vApp/vapp-1",
+        200,
+        "application/vnd.vmware.vcloud.task+json;version=36.0",
+        json!({"status": "queued"}),
+    );
+    update["request"]["postData"] = json!({
+        "mimeType": "application/vnd.vmware.vcloud.vApp+json;version=35.0",
+        "text": json!({"name": "web"}).to_string()
+    });
+    let har: Har = serde_json::from_value(json!({"log": {"entries": [
+        entry("GET", "https://vcd.example.com/api/vApp/vapp-1?x=1", 200, vapp, json!({"name": "web"})),
+        entry("GET", "https://vcd.example.com/api/vApp/vapp-1", 200, vapp, json!({"vdc": {"bogus": 1}})),
+        entry("GET", "https://vcd.example.com/api/vApp/vapp-1", 500, vapp, json!({})),
+        entry("GET", "https://vcd.example.com/api/nowhere/1", 404, vapp, json!({})),
+        entry("GET", "https://vcd.example.com/api/nowhere/2", 404, vapp, json!({})),
+        entry("DELETE", "https://vcd.example.com/api/query", 204, "", Value::Null),
+        entry("GET", "https://vcd.example.com/cloudapi/1.0.0/sites", 200, "application/json", json!({})),
+        entry("GET", "https://vcd.example.com/api/task/task-1", 200, "application/json", json!({})),
+        update,
+    ]}}))
+    .unwrap();
+
+    let report = check(&spec, &har);
+    assert_eq!((report.entries, report.ignored), (9, 1));
+    assert_eq!(
+        report.operations,
+        BTreeMap::from([
+            ("GET /task/{id}".to_string(), 1),
+            ("GET /vApp/{id}".to_string(), 3),
+            ("PUT /vApp/{id}".to_string(), 1)
+        ])
+    );
+    assert_eq!(
+        report.counts(),
+        BTreeMap::from([
+            (Problem::UndocumentedEndpoint, 2),
+            (Problem::UndocumentedMethod, 1),
+            (Problem::UnexpectedStatus, 1),
+            (Problem::UnexpectedContentType, 1),
+            (Problem::VersionMismatch, 1),
+            (Problem::ResponseBody(ViolationKind::Required), 1),
+            (Problem::ResponseBody(ViolationKind::Unexpected), 1),
+        ])
+    );
+    assert_eq!(
+        report.undocumented(),
+        BTreeMap::from([
+            ("DELETE /query".to_string(), 1),
+            ("GET /nowhere/{id}".to_string(), 2)
+        ])
+    );
+    assert_eq!(
+        report.findings[1],
+        Finding {
+            entry: 1,
+            method: "GET".into(),
+            path: "/vApp/vapp-1".into(),
+            problem: Problem::ResponseBody(ViolationKind::Unexpected),
+            message: "/vdc: the property bogus is not in the schema".into()
+        }
+    );
+    assert!(report
+        .to_string()
+        .contains("9 entries, 1 not below /api, 3 operations seen\n"));
+}
+
+#[test]
+fn check_documented_schema_test() {
+    let mut spec: Value = serde_json::from_str(include_str!("codegen/test_spec.json")).unwrap();
+    let task = "application/vnd.vmware.vcloud.task+json;version=36.0";
+    // The schema the operation documents wins over the one of the media type.
+    spec["paths"]["/task/{id}"]["get"]["responses"]["2XX"]["content"][task]["schema"] =
+        json!({"type": "object", "required": ["owner"]});
+    let spec: OpenAPI = serde_json::from_value(spec).unwrap();
+    let har: Har = serde_json::from_value(json!({"log": {"entries": [
+        entry("GET", "https://vcd.example.com/api/task/task-1", 200, task, json!({"status": "queued"})),
+    ]}}))
+    .unwrap();
+
+    let report = check(&spec, &har);
+    assert_eq!(
+        report.counts(),
+        BTreeMap::from([(Problem::ResponseBody(ViolationKind::Required), 1)])
+    );
+}
@@ -0,0 +1,549 @@
+//! The FIQL-like language of the query service's `filter` parameter.
+//!
+//! Conditions are `attribute<operator>value`, combined with `;` (and) and
+//! `,` (or), where and binds tighter and parentheses group. Values holding
+//! one of the reserved characters have to be percent-encoded, with
+//! `filterEncoded=true` sent alongside the filter.
+
+use crate::parsers::doc::query::Query;
+use regex::Regex;
+use std::fmt;
+use thiserror::Error;
+
+/// Characters that end a value unless percent-encoded.
+const RESERVED: &[char] = &[';', ',', '(', ')'];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FilterError {
+    #[error("Unexpected end of filter")]
+    UnexpectedEnd,
+    #[error("Unexpected character `{1}` at position {0}")]
+    UnexpectedCharacter(usize, char),
+    #[error("Missing attribute at position {0}")]
+    MissingAttribute(usize),
+    #[error("Missing value for `{0}`")]
+    MissingValue(String),
+    #[error("Invalid percent-encoding in value `{0}`")]
+    InvalidEncoding(String),
+    #[error("Query `{0}` has no attribute `{1}`")]
+    UnknownAttribute(String, String),
+    #[error("Attribute `{0}` is not filterable")]
+    NotFilterable(String),
+    #[error("Value `{2}` is not a valid {1} for `{0}`")]
+    InvalidValue(String, String, String),
+    #[error("Operator `{1}` cannot be used with `{0}`")]
+    InvalidOperator(String, Operator),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    const ALL: [Operator; 6] = [
+        Operator::Equal,
+        Operator::NotEqual,
+        Operator::LessThan,
+        Operator::LessOrEqual,
+        Operator::GreaterThan,
+        Operator::GreaterOrEqual,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::LessThan => "=lt=",
+            Operator::LessOrEqual => "=le=",
+            Operator::GreaterThan => "=gt=",
+            Operator::GreaterOrEqual => "=ge=",
+        }
+    }
+
+    fn is_ordering(self) -> bool {
+        !matches!(self, Operator::Equal | Operator::NotEqual)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Condition {
+        attribute: String,
+        operator: Operator,
+        /// The decoded value; `*` is a wildcard.
+        value: String,
+    },
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+/// A filter ready to be sent as the `filter` and `filterEncoded` parameters.
+#[derive(Debug, PartialEq)]
+pub struct RenderedFilter {
+    pub filter: String,
+    pub filter_encoded: bool,
+}
+
+impl Filter {
+    /// Parses a `filter` parameter, decoding values when `filter_encoded` is set.
+    pub fn parse(filter: &str, filter_encoded: bool) -> Result<Self, FilterError> {
+        let mut parser = Parser {
+            chars: filter.char_indices().collect(),
+            position: 0,
+            filter_encoded,
+        };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some((i, c)) => Err(FilterError::UnexpectedCharacter(i, c)),
+        }
+    }
+
+    pub fn condition(attribute: &str, operator: Operator, value: impl ToString) -> Self {
+        Filter::Condition {
+            attribute: attribute.to_string(),
+            operator,
+            value: value.to_string(),
+        }
+    }
+
+    pub fn eq(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::Equal, value)
+    }
+
+    pub fn ne(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::NotEqual, value)
+    }
+
+    pub fn lt(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::LessThan, value)
+    }
+
+    pub fn le(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::LessOrEqual, value)
+    }
+
+    pub fn gt(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::GreaterThan, value)
+    }
+
+    pub fn ge(attribute: &str, value: impl ToString) -> Self {
+        Self::condition(attribute, Operator::GreaterOrEqual, value)
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Renders the filter, percent-encoding values only when one of them needs it.
+    pub fn render(&self) -> RenderedFilter {
+        let filter_encoded = self.needs_encoding();
+        let mut filter = String::new();
+        self.write(&mut filter, filter_encoded);
+        RenderedFilter {
+            filter,
+            filter_encoded,
+        }
+    }
+
+    fn needs_encoding(&self) -> bool {
+        match self {
+            Filter::Condition { value, .. } => value.contains(RESERVED) || value.contains('%'),
+            Filter::And(filters) | Filter::Or(filters) => filters.iter().any(Self::needs_encoding),
+        }
+    }
+
+    fn write(&self, out: &mut String, filter_encoded: bool) {
+        match self {
+            Filter::Condition {
+                attribute,
+                operator,
+                value,
+            } => {
+                out.push_str(attribute);
+                out.push_str(operator.as_str());
+                if filter_encoded {
+                    out.push_str(&encode(value));
+                } else {
+                    out.push_str(value);
+                }
+            }
+            Filter::And(filters) => {
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        out.push(';');
+                    }
+                    if let Filter::Or(_) = filter {
+                        out.push('(');
+                        filter.write(out, filter_encoded);
+                        out.push(')');
+                    } else {
+                        filter.write(out, filter_encoded);
+                    }
+                }
+            }
+            Filter::Or(filters) => {
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    filter.write(out, filter_encoded);
+                }
+            }
+        }
+    }
+
+    /// Checks the attributes against a query type: they have to exist, be
+    /// filterable and be compared with values of their documented type.
+    pub fn validate(&self, query: &Query) -> Result<(), FilterError> {
+        match self {
+            Filter::Condition {
+                attribute,
+                operator,
+                value,
+            } => {
+                // Metadata filters are not part of the documented attributes.
+                if attribute.starts_with("metadata:") || attribute.starts_with("metadata@SYSTEM:") {
+                    return Ok(());
+                }
+                let a = query
+                    .attributes
+                    .iter()
+                    .find(|a| a.name == *attribute)
+                    .ok_or_else(|| {
+                        FilterError::UnknownAttribute(query.name.clone(), attribute.clone())
+                    })?;
+                if !a.filterable {
+                    return Err(FilterError::NotFilterable(attribute.clone()));
+                }
+                validate_value(attribute, &a.r#type, *operator, value)
+            }
+            Filter::And(filters) | Filter::Or(filters) => {
+                filters.iter().try_for_each(|f| f.validate(query))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render().filter)
+    }
+}
+
+fn validate_value(
+    attribute: &str,
+    r#type: &str,
+    operator: Operator,
+    value: &str,
+) -> Result<(), FilterError> {
+    lazy_static! {
+        static ref DATE_TIME: Regex =
+            Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?)?$")
+                .unwrap();
+    }
+    let r#type = r#type.to_ascii_lowercase();
+    let valid = match r#type.as_str() {
+        "boolean" => {
+            if operator.is_ordering() {
+                return Err(FilterError::InvalidOperator(
+                    attribute.to_string(),
+                    operator,
+                ));
+            }
+            value == "true" || value == "false"
+        }
+        "int" | "integer" | "short" | "long" => value.parse::<i64>().is_ok(),
+        "decimal" | "double" | "float" => value.parse::<f64>().is_ok(),
+        "date" | "datetime" => DATE_TIME.is_match(value),
+        // Strings accept wildcards, but only for equality.
+        _ => !(operator.is_ordering() && value.contains('*')),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(FilterError::InvalidValue(
+            attribute.to_string(),
+            r#type,
+            value.to_string(),
+        ))
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and the wildcard.
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'*' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn decode(value: &str) -> Result<String, FilterError> {
+    let error = || FilterError::InvalidEncoding(value.to_string());
+    let mut bytes = Vec::new();
+    let mut rest = value.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = tail.get(..2).ok_or_else(error)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| error())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| error())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| error())
+}
+
+struct Parser {
+    chars: Vec<(usize, char)>,
+    position: usize,
+    filter_encoded: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, char)> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i).map(|(_, p)| *p) == Some(c))
+    }
+
+    fn or(&mut self) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.and()?];
+        while let Some((_, ',')) = self.peek() {
+            self.position += 1;
+            filters.push(self.and()?);
+        }
+        Ok(flatten(filters, Filter::Or))
+    }
+
+    fn and(&mut self) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.primary()?];
+        while let Some((_, ';')) = self.peek() {
+            self.position += 1;
+            filters.push(self.primary()?);
+        }
+        Ok(flatten(filters, Filter::And))
+    }
+
+    fn primary(&mut self) -> Result<Filter, FilterError> {
+        match self.peek() {
+            Some((_, '(')) => {
+                self.position += 1;
+                let filter = self.or()?;
+                match self.peek() {
+                    Some((_, ')')) => {
+                        self.position += 1;
+                        Ok(filter)
+                    }
+                    Some((i, c)) => Err(FilterError::UnexpectedCharacter(i, c)),
+                    None => Err(FilterError::UnexpectedEnd),
+                }
+            }
+            Some(_) => self.condition(),
+            None => Err(FilterError::UnexpectedEnd),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Filter, FilterError> {
+        let start = self.position;
+        let mut attribute = String::new();
+        let operator = loop {
+            if let Some(operator) = Operator::ALL.iter().find(|o| self.starts_with(o.as_str())) {
+                break *operator;
+            }
+            match self.peek() {
+                Some((i, c)) if RESERVED.contains(&c) || c == '=' || c == '!' => {
+                    return Err(FilterError::UnexpectedCharacter(i, c))
+                }
+                Some((_, c)) => attribute.push(c),
+                None => return Err(FilterError::UnexpectedEnd),
+            }
+            self.position += 1;
+        };
+        if attribute.is_empty() {
+            return Err(FilterError::MissingAttribute(
+                self.chars.get(start).map_or(0, |(i, _)| *i),
+            ));
+        }
+        self.position += operator.as_str().len();
+        let mut value = String::new();
+        while let Some((_, c)) = self.peek().filter(|(_, c)| !RESERVED.contains(c)) {
+            value.push(c);
+            self.position += 1;
+        }
+        if value.is_empty() {
+            return Err(FilterError::MissingValue(attribute));
+        }
+        let value = if self.filter_encoded {
+            decode(&value)?
+        } else {
+            value
+        };
+        Ok(Filter::Condition {
+            attribute,
+            operator,
+            value,
+        })
+    }
+}
+
+fn flatten(mut filters: Vec<Filter>, combine: fn(Vec<Filter>) -> Filter) -> Filter {
+    if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        combine(filters)
+    }
+}
+
+#[test]
+fn parse_filter_test() {
+    assert_eq!(
+        Filter::parse("name==a*;(numberOfVMs=gt=3,isEnabled!=false)", false),
+        Ok(Filter::eq("name", "a*")
+            .and(Filter::gt("numberOfVMs", 3).or(Filter::ne("isEnabled", false))))
+    );
+    assert_eq!(
+        Filter::parse("name==a%3Bb%20c", true),
+        Ok(Filter::eq("name", "a;b c"))
+    );
+    assert_eq!(
+        Filter::parse("name==a;", false),
+        Err(FilterError::UnexpectedEnd)
+    );
+    assert_eq!(
+        Filter::parse("==a", false),
+        Err(FilterError::MissingAttribute(0))
+    );
+    assert_eq!(
+        Filter::parse("name==", false),
+        Err(FilterError::MissingValue("name".into()))
+    );
+    assert_eq!(
+        Filter::parse("name==a)", false),
+        Err(FilterError::UnexpectedCharacter(7, ')'))
+    );
+    assert_eq!(
+        Filter::parse("name==%zz", true),
+        Err(FilterError::InvalidEncoding("%zz".into()))
+    );
+}
+
+#[test]
+fn render_filter_test() {
+    let filter = Filter::eq("name", "a*")
+        .and(Filter::gt("numberOfVMs", 3).or(Filter::ne("isEnabled", false)));
+    assert_eq!(
+        filter.render(),
+        RenderedFilter {
+            filter: "name==a*;(numberOfVMs=gt=3,isEnabled!=false)".into(),
+            filter_encoded: false,
+        }
+    );
+    let filter = Filter::eq("name", "a;b c").or(Filter::le("creationDate", "2020-01-01"));
+    let rendered = filter.render();
+    assert_eq!(
+        rendered,
+        RenderedFilter {
+            filter: "name==a%3Bb%20c,creationDate=le=2020-01-01".into(),
+            filter_encoded: true,
+        }
+    );
+    assert_eq!(Filter::parse(&rendered.filter, true), Ok(filter));
+}
+
+#[test]
+fn validate_filter_test() {
+    use std::convert::TryFrom;
+    let query = Query::try_from(include_str!("parsers/doc/queries/adminVApp.html")).unwrap();
+    let valid = Filter::parse(
+        "name==a*;isEnabled==true;creationDate=ge=2020-01-01T00:00:00Z;metadata:x==STRING:y",
+        false,
+    )
+    .unwrap();
+    assert_eq!(valid.validate(&query), Ok(()));
+    assert_eq!(
+        Filter::eq("metadata@SYSTEM:x", "STRING:y").validate(&query),
+        Ok(())
+    );
+    assert_eq!(
+        Filter::eq("metadataFoo", "x").validate(&query),
+        Err(FilterError::UnknownAttribute(
+            "adminVApp".into(),
+            "metadataFoo".into()
+        ))
+    );
+    assert_eq!(
+        Filter::eq("owner", "x").validate(&query),
+        Err(FilterError::UnknownAttribute(
+            "adminVApp".into(),
+            "owner".into()
+        ))
+    );
+    assert_eq!(
+        Filter::eq("numberOfVMs", 1).validate(&query),
+        Err(FilterError::NotFilterable("numberOfVMs".into()))
+    );
+    assert_eq!(
+        Filter::eq("isEnabled", "yes").validate(&query),
+        Err(FilterError::InvalidValue(
+            "isEnabled".into(),
+            "boolean".into(),
+            "yes".into()
+        ))
+    );
+    assert_eq!(
+        Filter::gt("isEnabled", true).validate(&query),
+        Err(FilterError::InvalidOperator(
+            "isEnabled".into(),
+            Operator::GreaterThan
+        ))
+    );
+    assert_eq!(
+        Filter::lt("name", "a*").validate(&query),
+        Err(FilterError::InvalidValue(
+            "name".into(),
+            "string".into(),
+            "a*".into()
+        ))
+    );
+}
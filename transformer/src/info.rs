@@ -12,9 +12,13 @@ pub fn info<R: Read + Seek>(zip: &mut ZipArchive<R>, title: String) -> Result<In
             .context("Unable to read commonRes.js")?;
         javascript
     })?;
+    let version = common_res.version_information;
     Ok(Info {
         title,
-        version: common_res.version_information,
+        description: Some(version_description(
+            version.split_whitespace().last().unwrap_or_default(),
+        )),
+        version,
         license: Some(License {
             name: common_res.copyright,
             ..Default::default()
@@ -22,3 +26,24 @@ pub fn info<R: Read + Seek>(zip: &mut ZipArchive<R>, title: String) -> Result<In
         ..Default::default()
     })
 }
+
+/// Where requests say which API version they want. OpenAPI ignores `Accept`
+/// header parameters, so this can't be described as one.
+fn version_description(api_version: &str) -> String {
+    format!(
+        "Every request chooses the API version with the `version` parameter of \
+         its `Accept` header, such as `Accept: application/*+json;version={0}`. \
+         The media types in this definition carry `version={0}` too.",
+        api_version
+    )
+}
+
+/// The API version of a spec, such as `39.1` of `Version 39.1`.
+pub fn api_version(info: &Info) -> &str {
+    info.version.split_whitespace().last().unwrap_or_default()
+}
+
+#[test]
+fn version_description_test() {
+    assert!(version_description("39.1").contains("`Accept: application/*+json;version=39.1`"));
+}
@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate unhtml_derive;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate indexmap;
+
+pub mod augment;
+pub mod codegen;
+pub mod conformance;
+pub mod filter;
+pub mod info;
+pub mod mock;
+pub mod operation_ids;
+pub mod overlay;
+pub mod parsers;
+pub mod paths;
+pub mod queries;
+pub mod responses;
+pub mod schema_tweaks;
+pub mod schemas;
+pub mod spec;
+pub mod types;
+pub mod validate;
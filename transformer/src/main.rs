@@ -1,44 +1,155 @@
 #[macro_use]
 extern crate log;
 #[macro_use]
-extern crate unhtml_derive;
-#[macro_use]
-extern crate lazy_static;
+extern crate indexmap;
 
+use anyhow::{bail, Context, Result};
 use indexmap::IndexMap;
 use openapiv3::{Components, OpenAPI, ReferenceOr, SecurityScheme, Tag};
-use schema_tweaks::{query_parameters::query_parameters};
-use std::{collections::BTreeMap, io::Read};
-mod info;
-mod parsers;
-mod paths;
-mod queries;
-mod schema_tweaks;
-mod schemas;
-mod types;
-use anyhow::{Context, Result};
+use std::{collections::BTreeMap, io::Read, net::TcpListener, path::Path};
+use transformer::mock::Mock;
+use transformer::parsers::doc::etc::model::Model;
+use transformer::schema_tweaks::{
+    discriminators::codegen_discriminators,
+    header_parameters::header_parameters,
+    links::links,
+    query_parameters::query_parameters,
+    query_records::{query_records, QUERIES_EXTENSION},
+    query_results::query_results,
+    response_headers::response_headers,
+};
+use transformer::{codegen, info, overlay, parsers, paths, queries, responses, schemas, types};
 
-#[macro_use]
-extern crate indexmap;
+const USAGE: &str =
+    "Usage: transformer [--overlays <dir>] [--codegen-discriminators] [codegen (rust | rust-xml) <output-dir> [<crate-name>]] < doc.zip
+       transformer mock <address> <spec.json> [<scenario.json>]
+       transformer conformance <spec.json> <capture.har>
+       transformer augment <spec.json> <capture.har>...";
 
 fn main() -> Result<()> {
     env_logger::init();
     info!("starting up");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut args = args.as_slice();
+    let mut corrections = Vec::new();
+    let mut discriminators = false;
+    loop {
+        match args {
+            ["--overlays", dir, rest @ ..] => {
+                corrections = overlay::load(Path::new(dir))?;
+                args = rest;
+            }
+            ["--codegen-discriminators", rest @ ..] => {
+                discriminators = true;
+                args = rest;
+            }
+            _ => break,
+        }
+    }
+    match args {
+        [] | ["codegen", "rust" | "rust-xml", _] | ["codegen", "rust" | "rust-xml", _, _] => {}
+        ["mock", address, spec_path] => return mock(address, spec_path, None),
+        ["mock", address, spec_path, scenario_path] => {
+            return mock(address, spec_path, Some(scenario_path))
+        }
+        ["conformance", spec_path, har_path] => return conformance(spec_path, har_path),
+        ["augment", spec_path, har_paths @ ..] if !har_paths.is_empty() => {
+            return augment(spec_path, har_paths)
+        }
+        _ => bail!(USAGE),
+    }
+
     let mut zip_buffer = Vec::new();
     std::io::stdin()
         .read_to_end(&mut zip_buffer)
         .context("Unable to read zip file")?;
+    let (spec, model) = spec(zip_buffer)?;
+    let mut spec = overlay::apply(spec, &corrections)?;
+    if let (true, Some(components)) = (discriminators, &mut spec.components) {
+        codegen_discriminators(&mut components.schemas);
+    }
+
+    match args {
+        ["codegen", language, output_dir, crate_name @ ..] => {
+            let crate_name = crate_name.first().copied().unwrap_or("vcloud-models");
+            let files = match *language {
+                "rust" => codegen::rust::generate(&spec, crate_name),
+                _ => model.rust_types(
+                    &format!("{} {}", spec.info.title, spec.info.version),
+                    crate_name,
+                ),
+            };
+            codegen::write(&files, Path::new(output_dir))
+                .with_context(|| format!("Unable to write crate to {}", output_dir))?;
+        }
+        _ => {
+            serde_json::to_writer_pretty(std::io::stdout(), &spec)
+                .context("Unable to write JSON")?;
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// Serves a mock of the API described by an already generated definition,
+/// remembering entities when given a scenario to start from.
+fn mock(address: &str, spec_path: &str, scenario_path: Option<&str>) -> Result<()> {
+    let mut mock = Mock::new(read_json(spec_path)?);
+    if let Some(scenario_path) = scenario_path {
+        mock = mock.with_scenario(read_json(scenario_path)?);
+    }
+    let listener =
+        TcpListener::bind(address).with_context(|| format!("Unable to listen on {}", address))?;
+    info!("serving a mock of {} on {}", spec_path, address);
+    mock.serve(listener).context("Unable to serve mock")
+}
 
+/// Checks captured traffic against an already generated definition, failing
+/// if anything disagrees.
+fn conformance(spec_path: &str, har_path: &str) -> Result<()> {
+    let report = transformer::conformance::check(&read_json(spec_path)?, &read_json(har_path)?);
+    print!("{}", report);
+    if !report.findings.is_empty() {
+        bail!("{} findings", report.findings.len());
+    }
+    Ok(())
+}
+
+/// Writes an overlay of what captured traffic shows an already generated
+/// definition lacks.
+fn augment(spec_path: &str, har_paths: &[&str]) -> Result<()> {
+    let hars = har_paths
+        .iter()
+        .map(|path| read_json(path))
+        .collect::<Result<Vec<_>>>()?;
+    let overlay = transformer::augment::augment(&read_json(spec_path)?, &hars)?;
+    serde_json::to_writer_pretty(std::io::stdout(), &overlay).context("Unable to write JSON")?;
+    println!();
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T> {
+    serde_json::from_reader(std::io::BufReader::new(
+        std::fs::File::open(path).with_context(|| format!("Unable to open {}", path))?,
+    ))
+    .with_context(|| format!("Unable to parse {}", path))
+}
+
+/// Builds the OpenAPI definition, and the model of the XSDs it was made
+/// from, from the documentation zip.
+fn spec(zip_buffer: Vec<u8>) -> Result<(OpenAPI, Model)> {
     let mut zip = zip::ZipArchive::new(std::io::Cursor::new(zip_buffer))
         .context("Unable to parse zip file")?;
 
     let mut schemas = IndexMap::new();
-    query_parameters(
-        &mut schemas,
-        &queries::queries(&mut zip).context("unable to collect queries")?,
-    );
-    let content_type_mapping =
-        schemas::schemas(&mut schemas, &mut zip).context("Unable to make content type mappings")?;
+    let queries = queries::queries(&mut zip).context("unable to collect queries")?;
+    query_parameters(&mut schemas, &queries);
+    let mut model = Model::default();
+    let content_type_mapping = schemas::schemas(&mut schemas, &mut zip, &mut model)
+        .context("Unable to make content type mappings")?;
+    let media_types = schemas::media_types(&content_type_mapping);
+    let mut query_metadata = query_records(&schemas, &queries);
 
     let content_element_mapping: BTreeMap<String, String> = types::types(&mut zip)
         .context("unable to collect types")?
@@ -51,7 +162,7 @@ fn main() -> Result<()> {
         })
         .collect();
 
-    let about_info = crate::parsers::about::parse(&{
+    let about_info = parsers::about::parse(&{
         let mut html = String::new();
         zip.by_name("about.html")?
             .read_to_string(&mut html)
@@ -68,26 +179,39 @@ fn main() -> Result<()> {
         .context("Couldn't determine version")?
         .to_string();
 
+    let mut parameters = header_parameters();
+    let mut paths = paths::paths(
+        &mut zip,
+        content_type_mapping,
+        content_element_mapping,
+        api_version.clone(),
+        &mut parameters,
+        &model,
+    )
+    .context("Unable to collect paths")?;
+    for (query, results) in query_results(&mut paths, &mut schemas, &queries, &api_version) {
+        query_metadata[&query]["results"] = results;
+    }
+    links(&mut paths, &media_types);
+
     let spec = OpenAPI {
         openapi: "3.0.2".into(),
         info,
         components: Some(Components {
             schemas,
+            responses: responses::responses(&api_version),
+            parameters,
+            headers: response_headers(),
             security_schemes: indexmap! {
                 "basicAuth".into() => ReferenceOr::Item(
                     SecurityScheme::HTTP {scheme:"basic".into(),bearer_format:None, description: None }),
                 "bearerAuth".into() => ReferenceOr::Item(
                     SecurityScheme::HTTP {scheme:"bearer".into(),bearer_format:None, description: None })
             },
+            extensions: indexmap! { schemas::MEDIA_TYPES_EXTENSION.into() => media_types },
             ..Default::default()
         }),
-        paths: paths::paths(
-            &mut zip,
-            content_type_mapping,
-            content_element_mapping,
-            api_version,
-        )
-        .context("Unable to collect paths")?,
+        paths,
         tags: vec![
             Tag {
                 name: "user".into(),
@@ -108,9 +232,8 @@ fn main() -> Result<()> {
                 extensions: Default::default(),
             },
         ],
+        extensions: indexmap! { QUERIES_EXTENSION.into() => query_metadata },
         ..Default::default()
     };
-    serde_json::to_writer_pretty(std::io::stdout(), &spec).context("Unable to write JSON")?;
-    println!();
-    Ok(())
+    Ok((spec, model))
 }
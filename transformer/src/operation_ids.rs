@@ -0,0 +1,208 @@
+use crate::parsers::doc::operation::Method;
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Hand-picked operationIds keyed by `"<METHOD> <path>"`. These win over the
+/// generated names, so SDK method names can be pinned across API versions.
+const OVERRIDES: &str = include_str!("operation_id_overrides.json");
+
+pub struct OperationIds {
+    overrides: BTreeMap<String, String>,
+    used: BTreeSet<String>,
+    /// How many registered operations each candidate id could stand for.
+    candidates: BTreeMap<String, usize>,
+}
+
+impl OperationIds {
+    pub fn new() -> Result<Self> {
+        Self::with_overrides(
+            serde_json::from_str(OVERRIDES).context("Unable to parse operationId overrides")?,
+        )
+    }
+
+    pub fn with_overrides(overrides: BTreeMap<String, String>) -> Result<Self> {
+        let mut used = BTreeSet::new();
+        for id in overrides.values() {
+            if !used.insert(id.clone()) {
+                bail!("operationId override `{}` is used more than once", id);
+            }
+        }
+        Ok(Self {
+            overrides,
+            used,
+            candidates: BTreeMap::new(),
+        })
+    }
+
+    /// Keeps operationIds that are already taken from being assigned.
+    pub fn reserve(&mut self, ids: impl IntoIterator<Item = String>) {
+        self.used.extend(ids);
+    }
+
+    /// Registers an operation before ids are assigned, so that ids several
+    /// operations could get are given to none of them, whichever comes first.
+    pub fn register(&mut self, method: Method, path: &str, page_name: &str) {
+        for id in candidates(method, path, page_name) {
+            *self.candidates.entry(id).or_default() += 1;
+        }
+    }
+
+    /// Picks the operationId for an operation.
+    ///
+    /// The documented page name (`getVApp` for `GET-VApp`) is preferred, then
+    /// the method and path template (`getVAppById` for `GET /vApp/{id}`). An id
+    /// is only used if no other registered operation could get it, so adding
+    /// an operation never takes an id over from another one. When both are
+    /// shared, a suffix hashed from the method and path is appended.
+    pub fn assign(&mut self, method: Method, path: &str, page_name: &str) -> String {
+        let key = format!("{} {}", method.as_str(), path);
+        if let Some(id) = self.overrides.get(&key) {
+            return id.clone();
+        }
+        let id = candidates(method, path, page_name)
+            .into_iter()
+            .find(|id| {
+                self.candidates.get(id).copied().unwrap_or_default() <= 1 && !self.used.contains(id)
+            })
+            .unwrap_or_else(|| format!("{}{:08x}", from_path(method, path), fnv1a(&key) as u32));
+        self.used.insert(id.clone());
+        id
+    }
+}
+
+/// The ids an operation could get, best first.
+fn candidates(method: Method, path: &str, page_name: &str) -> Vec<String> {
+    let mut candidates = from_page_name(method, page_name)
+        .into_iter()
+        .collect::<Vec<_>>();
+    let from_path = from_path(method, path);
+    if !candidates.contains(&from_path) {
+        candidates.push(from_path);
+    }
+    candidates
+}
+
+fn from_page_name(method: Method, page_name: &str) -> Option<String> {
+    let name = page_name
+        .strip_prefix(method.as_str())
+        .and_then(|n| n.strip_prefix('-'))
+        .unwrap_or(page_name);
+    let name = upper_camel_case(name);
+    if name.is_empty() {
+        None
+    } else {
+        Some(format!("{}{}", method.as_str().to_ascii_lowercase(), name))
+    }
+}
+
+fn from_path(method: Method, path: &str) -> String {
+    path.split('/').filter(|s| !s.is_empty()).fold(
+        method.as_str().to_ascii_lowercase(),
+        |mut acc, segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => {
+                acc.push_str("By");
+                acc.push_str(&upper_camel_case(param));
+                acc
+            }
+            None => {
+                acc.push_str(&upper_camel_case(segment));
+                acc
+            }
+        },
+    )
+}
+
+/// A hash that stays the same across platforms and Rust versions.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Joins the alphanumeric runs of s, capitalizing the first letter of each.
+fn upper_camel_case(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut c = w.chars();
+            match c.next() {
+                None => String::new(),
+                Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn operation_id_from_page_name_test() {
+    let mut ids = OperationIds::with_overrides(BTreeMap::new()).unwrap();
+    assert_eq!(ids.assign(Method::Get, "/vApp/{id}", "GET-VApp"), "getVApp");
+    assert_eq!(
+        ids.assign(Method::Post, "/vApp/{id}/action/deploy", "POST-DeployVApp"),
+        "postDeployVApp"
+    );
+}
+
+#[test]
+fn operation_id_from_path_test() {
+    let mut ids = OperationIds::with_overrides(BTreeMap::new()).unwrap();
+    assert_eq!(
+        ids.assign(Method::Get, "/admin/org/{id}/vdcs", ""),
+        "getAdminOrgByIdVdcs"
+    );
+}
+
+#[test]
+fn operation_id_collision_test() {
+    let assign_all = |operations: &[(&str, &str)]| {
+        let mut ids = OperationIds::with_overrides(BTreeMap::new()).unwrap();
+        for (path, page_name) in operations {
+            ids.register(Method::Get, path, page_name);
+        }
+        operations
+            .iter()
+            .map(|(path, page_name)| ids.assign(Method::Get, path, page_name))
+            .collect::<Vec<_>>()
+    };
+    // Neither operation keeps a page name they share, in either order.
+    assert_eq!(
+        assign_all(&[
+            ("/vApp/{id}", "GET-VApp"),
+            ("/vAppTemplate/{id}", "GET-VApp")
+        ]),
+        ["getVAppById", "getVAppTemplateById"]
+    );
+    assert_eq!(
+        assign_all(&[
+            ("/vAppTemplate/{id}", "GET-VApp"),
+            ("/vApp/{id}", "GET-VApp")
+        ]),
+        ["getVAppTemplateById", "getVAppById"]
+    );
+    // Paths differing only in punctuation both get a suffix.
+    assert_eq!(
+        assign_all(&[("/a-b", ""), ("/a_b", "")]),
+        [
+            format!("getAB{:08x}", fnv1a("GET /a-b") as u32),
+            format!("getAB{:08x}", fnv1a("GET /a_b") as u32)
+        ]
+    );
+}
+
+#[test]
+fn operation_id_override_test() {
+    let mut ids = OperationIds::with_overrides(
+        [("POST /sessions".to_string(), "login".to_string())]
+            .iter()
+            .cloned()
+            .collect(),
+    )
+    .unwrap();
+    assert_eq!(ids.assign(Method::Post, "/sessions", "POST-Login"), "login");
+    assert_eq!(ids.assign(Method::Post, "/other", "login"), "postLogin");
+}
+
+#[test]
+fn bundled_overrides_parse_test() {
+    assert!(OperationIds::new().is_ok());
+}
@@ -0,0 +1,785 @@
+//! Corrections to errors in the documentation, applied to the generated
+//! definition.
+//!
+//! A correction is either an [OpenAPI Overlay] whose actions have JSONPath
+//! targets, or a [JSON Patch] whose operations have JSON Pointer paths.
+//! Corrections are kept in a directory of subdirectories named for the range
+//! of API versions they apply to, such as `36.0-38.1` or `37.0-`. Every
+//! target has to exist, so that a correction the documentation no longer
+//! needs fails rather than silently doing nothing.
+//!
+//! [OpenAPI Overlay]: https://spec.openapis.org/overlay/v1.0.0.html
+//! [JSON Patch]: https://www.rfc-editor.org/rfc/rfc6902
+
+use crate::info::api_version;
+use anyhow::{Context, Result};
+use openapiv3::OpenAPI;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum OverlayError {
+    #[error("Invalid JSONPath `{0}` at position {1}")]
+    InvalidPath(String, usize),
+    #[error("Invalid JSON Pointer `{0}`")]
+    InvalidPointer(String),
+    #[error("Nothing matches `{0}`")]
+    NoMatch(String),
+    #[error("The action on `{0}` neither updates nor removes")]
+    NoChange(String),
+    #[error("The value at `{0}` isn't the one tested for")]
+    TestFailed(String),
+    #[error("Invalid version range `{0}`")]
+    InvalidRange(String),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Overlay {
+    pub overlay: String,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Action {
+    pub target: String,
+    pub description: Option<String>,
+    pub update: Option<Value>,
+    #[serde(default)]
+    pub remove: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+#[derive(Debug)]
+pub enum Document {
+    Overlay(Overlay),
+    Patch(Vec<PatchOperation>),
+}
+
+impl Document {
+    /// Reads an overlay, or a patch when the JSON is an array.
+    pub fn parse(text: &str) -> serde_json::Result<Self> {
+        let value: Value = serde_json::from_str(text)?;
+        Ok(if value.is_array() {
+            Document::Patch(serde_json::from_value(value)?)
+        } else {
+            Document::Overlay(serde_json::from_value(value)?)
+        })
+    }
+
+    pub fn apply(&self, value: &mut Value) -> Result<(), OverlayError> {
+        match self {
+            Document::Overlay(overlay) => overlay
+                .actions
+                .iter()
+                .try_for_each(|action| action.apply(value)),
+            Document::Patch(operations) => operations
+                .iter()
+                .try_for_each(|operation| operation.apply(value)),
+        }
+    }
+}
+
+impl Action {
+    fn apply(&self, value: &mut Value) -> Result<(), OverlayError> {
+        let mut matches = select(value, &parse_path(&self.target)?);
+        if matches.is_empty() {
+            return Err(OverlayError::NoMatch(self.target.clone()));
+        }
+        match (&self.update, self.remove) {
+            (Some(update), false) => {
+                for location in matches {
+                    if let Some(target) = get_mut(value, &location) {
+                        merge(target, update.clone());
+                    }
+                }
+            }
+            (None, true) => {
+                // Later array items go first so earlier indices stay valid.
+                matches.sort();
+                for location in matches.iter().rev() {
+                    remove(value, location);
+                }
+            }
+            _ => return Err(OverlayError::NoChange(self.target.clone())),
+        }
+        Ok(())
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, value: &mut Value) -> Result<(), OverlayError> {
+        match self {
+            PatchOperation::Add { path, value: added } => add(value, path, added.clone()),
+            PatchOperation::Remove { path } => take(value, path).map(drop),
+            PatchOperation::Replace {
+                path,
+                value: replacement,
+            } => {
+                let location = locate(value, path)?;
+                *get_mut(value, &location).ok_or_else(|| OverlayError::NoMatch(path.clone()))? =
+                    replacement.clone();
+                Ok(())
+            }
+            PatchOperation::Move { from, path } => {
+                let moved = take(value, from)?;
+                add(value, path, moved)
+            }
+            PatchOperation::Copy { from, path } => {
+                let location = locate(value, from)?;
+                let copied = get(value, &location)
+                    .ok_or_else(|| OverlayError::NoMatch(from.clone()))?
+                    .clone();
+                add(value, path, copied)
+            }
+            PatchOperation::Test {
+                path,
+                value: expected,
+            } => {
+                if get(value, &locate(value, path)?) == Some(expected) {
+                    Ok(())
+                } else {
+                    Err(OverlayError::TestFailed(path.clone()))
+                }
+            }
+        }
+    }
+}
+
+/// A step from a JSON value to one inside it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    /// `[?(@.a.b == value)]`: the items whose value at the path is equal.
+    Filter(Vec<String>, Value),
+}
+
+/// Parses the JSONPath used by overlays: names, indices, wildcards and
+/// equality filters, without recursive descent.
+fn parse_path(path: &str) -> Result<Vec<Selector>, OverlayError> {
+    let mut parser = Parser { path, position: 0 };
+    parser.expect('$')?;
+    let mut selectors = Vec::new();
+    while let Some(c) = parser.peek() {
+        match c {
+            '.' => {
+                parser.position += 1;
+                selectors.push(match parser.peek() {
+                    Some('*') => {
+                        parser.position += 1;
+                        Selector::Wildcard
+                    }
+                    _ => Selector::Name(parser.name()?),
+                });
+            }
+            '[' => selectors.push(parser.bracket()?),
+            _ => return Err(parser.error()),
+        }
+    }
+    Ok(selectors)
+}
+
+struct Parser<'a> {
+    path: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.path[self.position..].chars().next()
+    }
+
+    fn error(&self) -> OverlayError {
+        OverlayError::InvalidPath(self.path.to_string(), self.position)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), OverlayError> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error());
+        }
+        self.position += expected.len_utf8();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// A name after a `.`, up to the next `.`, `[` or operator.
+    fn name(&mut self) -> Result<String, OverlayError> {
+        let rest = &self.path[self.position..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '$'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error());
+        }
+        self.position += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn quoted(&mut self) -> Result<String, OverlayError> {
+        let quote = self.peek().ok_or_else(|| self.error())?;
+        self.position += 1;
+        let mut string = String::new();
+        let mut chars = self.path[self.position..].chars();
+        loop {
+            let c = chars.next().ok_or_else(|| self.error())?;
+            self.position += c.len_utf8();
+            match c {
+                '\\' => {
+                    let escaped = chars.next().ok_or_else(|| self.error())?;
+                    self.position += escaped.len_utf8();
+                    string.push(escaped);
+                }
+                c if c == quote => return Ok(string),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn bracket(&mut self) -> Result<Selector, OverlayError> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        let selector = match self.peek().ok_or_else(|| self.error())? {
+            '*' => {
+                self.position += 1;
+                Selector::Wildcard
+            }
+            '\'' | '"' => Selector::Name(self.quoted()?),
+            '?' => {
+                self.position += 1;
+                self.skip_whitespace();
+                let parenthesised = self.peek() == Some('(');
+                if parenthesised {
+                    self.position += 1;
+                }
+                let filter = self.filter()?;
+                if parenthesised {
+                    self.expect(')')?;
+                }
+                filter
+            }
+            _ => {
+                let rest = &self.path[self.position..];
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '-'))
+                    .unwrap_or(rest.len());
+                let index = rest[..end].parse().map_err(|_| self.error())?;
+                self.position += end;
+                Selector::Index(index)
+            }
+        };
+        self.expect(']')?;
+        Ok(selector)
+    }
+
+    fn filter(&mut self) -> Result<Selector, OverlayError> {
+        self.expect('@')?;
+        let mut path = Vec::new();
+        loop {
+            match self.peek() {
+                Some('.') => {
+                    self.position += 1;
+                    path.push(self.name()?);
+                }
+                Some('[') => match self.bracket()? {
+                    Selector::Name(name) => path.push(name),
+                    _ => return Err(self.error()),
+                },
+                _ => break,
+            }
+        }
+        self.expect('=')?;
+        self.expect('=')?;
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some('\'' | '"') => Value::String(self.quoted()?),
+            _ => {
+                let rest = &self.path[self.position..];
+                let end = rest
+                    .find(|c: char| c == ')' || c == ']' || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                let value = serde_json::from_str(&rest[..end]).map_err(|_| self.error())?;
+                self.position += end;
+                value
+            }
+        };
+        Ok(Selector::Filter(path, value))
+    }
+}
+
+/// The locations of the values the selectors pick.
+fn select(root: &Value, selectors: &[Selector]) -> Vec<Vec<Segment>> {
+    let mut nodes = vec![(Vec::new(), root)];
+    for selector in selectors {
+        nodes = nodes
+            .into_iter()
+            .flat_map(|(location, value)| {
+                children(value)
+                    .into_iter()
+                    .filter(|(segment, child)| match (selector, segment) {
+                        (Selector::Name(name), Segment::Key(key)) => name == key,
+                        (Selector::Index(i), Segment::Index(index)) => {
+                            let len = value.as_array().map_or(0, Vec::len) as i64;
+                            (if *i < 0 { len + i } else { *i }) == *index as i64
+                        }
+                        (Selector::Wildcard, _) => true,
+                        (Selector::Filter(path, expected), _) => path
+                            .iter()
+                            .try_fold(*child, |v, key| v.get(key))
+                            .is_some_and(|v| v == expected),
+                        _ => false,
+                    })
+                    .map(|(segment, child)| {
+                        let mut location = location.clone();
+                        location.push(segment);
+                        (location, child)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    nodes.into_iter().map(|(location, _)| location).collect()
+}
+
+fn children(value: &Value) -> Vec<(Segment, &Value)> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, child)| (Segment::Key(key.clone()), child))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (Segment::Index(i), child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn get<'a>(value: &'a Value, location: &[Segment]) -> Option<&'a Value> {
+    location
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Key(key) => value.as_object()?.get(key),
+            Segment::Index(i) => value.as_array()?.get(*i),
+        })
+}
+
+fn get_mut<'a>(value: &'a mut Value, location: &[Segment]) -> Option<&'a mut Value> {
+    location
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Key(key) => value.as_object_mut()?.get_mut(key),
+            Segment::Index(i) => value.as_array_mut()?.get_mut(*i),
+        })
+}
+
+fn remove(value: &mut Value, location: &[Segment]) -> Option<Value> {
+    let (last, parent) = location.split_last()?;
+    match (get_mut(value, parent)?, last) {
+        (Value::Object(object), Segment::Key(key)) => {
+            // Retained rather than removed from, which would reorder the keys.
+            let removed = object.get(key).cloned();
+            object.retain(|k, _| k != key);
+            removed
+        }
+        (Value::Array(items), Segment::Index(i)) if *i < items.len() => Some(items.remove(*i)),
+        _ => None,
+    }
+}
+
+/// Merges an overlay's update into a value: objects recursively, arrays by
+/// appending and anything else by replacing.
+fn merge(target: &mut Value, update: Value) {
+    match (target, update) {
+        (Value::Object(target), Value::Object(update)) => {
+            for (key, value) in update {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(update)) => target.extend(update),
+        (Value::Array(target), update) => target.push(update),
+        (target, update) => *target = update,
+    }
+}
+
+/// The location a JSON Pointer refers to. Array indices stay keys until
+/// they're used, as `-` and object keys made of digits are valid too.
+fn parse_pointer(pointer: &str) -> Result<Vec<Segment>, OverlayError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tokens = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| OverlayError::InvalidPointer(pointer.to_string()))?;
+    Ok(tokens
+        .split('/')
+        .map(|token| Segment::Key(token.replace("~1", "/").replace("~0", "~")))
+        .collect())
+}
+
+/// Resolves the keys of a location against the arrays they're used on.
+fn resolve(value: &Value, location: Vec<Segment>) -> Option<Vec<Segment>> {
+    let mut resolved = Vec::new();
+    let mut current = value;
+    for segment in location {
+        let segment = match (current, segment) {
+            (Value::Array(_), Segment::Key(key)) => Segment::Index(key.parse().ok()?),
+            (_, segment) => segment,
+        };
+        current = get(current, std::slice::from_ref(&segment))?;
+        resolved.push(segment);
+    }
+    Some(resolved)
+}
+
+fn add(value: &mut Value, pointer: &str, added: Value) -> Result<(), OverlayError> {
+    let no_match = || OverlayError::NoMatch(pointer.to_string());
+    let mut location = parse_pointer(pointer)?;
+    let last = match location.pop() {
+        Some(Segment::Key(last)) => last,
+        _ => {
+            *value = added;
+            return Ok(());
+        }
+    };
+    let parent = resolve(value, location).ok_or_else(no_match)?;
+    match get_mut(value, &parent).ok_or_else(no_match)? {
+        Value::Object(object) => {
+            object.insert(last, added);
+        }
+        Value::Array(items) if last == "-" => items.push(added),
+        Value::Array(items) => match last.parse::<usize>() {
+            Ok(i) if i <= items.len() => items.insert(i, added),
+            _ => return Err(no_match()),
+        },
+        _ => return Err(no_match()),
+    }
+    Ok(())
+}
+
+/// The location of the value a JSON Pointer refers to, which has to exist.
+fn locate(value: &Value, pointer: &str) -> Result<Vec<Segment>, OverlayError> {
+    resolve(value, parse_pointer(pointer)?)
+        .ok_or_else(|| OverlayError::NoMatch(pointer.to_string()))
+}
+
+fn take(value: &mut Value, pointer: &str) -> Result<Value, OverlayError> {
+    let location = locate(value, pointer)?;
+    if location.is_empty() {
+        return Ok(std::mem::take(value));
+    }
+    remove(value, &location).ok_or_else(|| OverlayError::NoMatch(pointer.to_string()))
+}
+
+/// An inclusive range of API versions, open ended when a bound is missing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Versions {
+    from: Option<Vec<u32>>,
+    to: Option<Vec<u32>>,
+}
+
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+impl FromStr for Versions {
+    type Err = OverlayError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || OverlayError::InvalidRange(range.to_string());
+        let (from, to) = range.split_once('-').ok_or_else(invalid)?;
+        let bound = |version: &str| match version {
+            "" => Ok(None),
+            version => parse_version(version).map(Some).ok_or_else(invalid),
+        };
+        Ok(Versions {
+            from: bound(from)?,
+            to: bound(to)?,
+        })
+    }
+}
+
+impl Versions {
+    pub fn contains(&self, version: &str) -> bool {
+        let version = match parse_version(version) {
+            Some(version) => version,
+            None => return false,
+        };
+        self.from.as_ref().is_none_or(|from| &version >= from)
+            && self.to.as_ref().is_none_or(|to| &version <= to)
+    }
+}
+
+/// A correction read from a file, with the API versions it applies to.
+#[derive(Debug)]
+pub struct Correction {
+    pub name: String,
+    pub versions: Versions,
+    pub document: Document,
+}
+
+/// Reads the corrections of a directory, ordered by version range and then
+/// file name.
+pub fn load(dir: &Path) -> Result<Vec<Correction>> {
+    let mut files = Vec::new();
+    for range in std::fs::read_dir(dir).with_context(|| format!("Unable to read {:?}", dir))? {
+        let range = range?.path();
+        let versions = range
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .parse::<Versions>()?;
+        for file in
+            std::fs::read_dir(&range).with_context(|| format!("Unable to read {:?}", range))?
+        {
+            let file = file?.path();
+            if file.extension().is_some_and(|e| e == "json") {
+                files.push((file, versions.clone()));
+            }
+        }
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    files
+        .into_iter()
+        .map(|(file, versions)| {
+            let text = std::fs::read_to_string(&file)
+                .with_context(|| format!("Unable to read {:?}", file))?;
+            Ok(Correction {
+                name: file
+                    .strip_prefix(dir)
+                    .unwrap_or(&file)
+                    .to_string_lossy()
+                    .into_owned(),
+                versions,
+                document: Document::parse(&text)
+                    .with_context(|| format!("Unable to parse {:?}", file))?,
+            })
+        })
+        .collect()
+}
+
+/// Applies the corrections for the spec's API version.
+pub fn apply(spec: OpenAPI, corrections: &[Correction]) -> Result<OpenAPI> {
+    let version = api_version(&spec.info).to_string();
+    let corrections = corrections
+        .iter()
+        .filter(|c| c.versions.contains(&version))
+        .collect::<Vec<_>>();
+    if corrections.is_empty() {
+        return Ok(spec);
+    }
+    let mut value = serde_json::to_value(spec)?;
+    for correction in corrections {
+        info!("applying {} to version {}", correction.name, version);
+        correction.document.apply(&mut value).with_context(|| {
+            format!("Unable to apply {} to version {}", correction.name, version)
+        })?;
+    }
+    serde_json::from_value(value).context("Corrections made an invalid definition")
+}
+
+#[cfg(test)]
+use serde_json::json;
+
+#[test]
+fn parse_path_test() {
+    assert_eq!(
+        parse_path("$.paths['/vApp/{id}'].get.parameters[?(@.name == 'id')]"),
+        Ok(vec![
+            Selector::Name("paths".into()),
+            Selector::Name("/vApp/{id}".into()),
+            Selector::Name("get".into()),
+            Selector::Name("parameters".into()),
+            Selector::Filter(vec!["name".into()], json!("id")),
+        ])
+    );
+    assert_eq!(
+        parse_path("$.a[*].b[-1][?@.c==1]"),
+        Ok(vec![
+            Selector::Name("a".into()),
+            Selector::Wildcard,
+            Selector::Name("b".into()),
+            Selector::Index(-1),
+            Selector::Filter(vec!["c".into()], json!(1)),
+        ])
+    );
+    assert_eq!(
+        parse_path("$..a"),
+        Err(OverlayError::InvalidPath("$..a".into(), 2))
+    );
+}
+
+#[test]
+fn overlay_test() {
+    let mut value = json!({
+        "schemas": {
+            "b": {"properties": {"type": {"type": "string"}}, "required": ["type"]},
+            "a": {"enum": ["x", "y"]}
+        }
+    });
+    let overlay = Document::parse(
+        &json!({"overlay": "1.0.0", "actions": [
+            {"target": "$.schemas.b.properties", "update": {"name": {"type": "string"}}},
+            {"target": "$.schemas.b.required", "update": "name"},
+            {"target": "$.schemas.a.enum[?(@ == 'y')]", "remove": true},
+            {"target": "$.schemas['b'].properties.type", "remove": true}
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+    overlay.apply(&mut value).unwrap();
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        json!({
+            "schemas": {
+                "b": {"properties": {"name": {"type": "string"}}, "required": ["type", "name"]},
+                "a": {"enum": ["x"]}
+            }
+        })
+        .to_string()
+    );
+
+    let missing = Document::parse(
+        &json!({"overlay": "1.0.0", "actions": [{"target": "$.schemas.c", "remove": true}]})
+            .to_string(),
+    )
+    .unwrap();
+    assert_eq!(
+        missing.apply(&mut value),
+        Err(OverlayError::NoMatch("$.schemas.c".into()))
+    );
+}
+
+#[test]
+fn patch_test() {
+    let mut value = json!({"a": {"b": [1, 2]}, "c/d": true});
+    let patch = Document::parse(
+        &json!([
+            {"op": "test", "path": "/c~1d", "value": true},
+            {"op": "add", "path": "/a/b/1", "value": 3},
+            {"op": "add", "path": "/a/b/-", "value": 4},
+            {"op": "replace", "path": "/a/b/0", "value": 0},
+            {"op": "copy", "from": "/a/b", "path": "/e"},
+            {"op": "move", "from": "/c~1d", "path": "/a/f"},
+            {"op": "remove", "path": "/e/3"}
+        ])
+        .to_string(),
+    )
+    .unwrap();
+    patch.apply(&mut value).unwrap();
+    assert_eq!(
+        value,
+        json!({"a": {"b": [0, 3, 2, 4], "f": true}, "e": [0, 3, 2]})
+    );
+
+    for (operation, error) in [
+        (
+            json!({"op": "replace", "path": "/g", "value": 1}),
+            OverlayError::NoMatch("/g".into()),
+        ),
+        (
+            json!({"op": "add", "path": "/g/h", "value": 1}),
+            OverlayError::NoMatch("/g/h".into()),
+        ),
+        (
+            json!({"op": "test", "path": "/e/0", "value": 1}),
+            OverlayError::TestFailed("/e/0".into()),
+        ),
+        (
+            json!({"op": "remove", "path": "e"}),
+            OverlayError::InvalidPointer("e".into()),
+        ),
+    ] {
+        let patch = Document::parse(&json!([operation]).to_string()).unwrap();
+        assert_eq!(patch.apply(&mut value), Err(error));
+    }
+}
+
+#[test]
+fn versions_test() {
+    let range = "36.0-38.1".parse::<Versions>().unwrap();
+    assert!(range.contains("36.0"));
+    assert!(range.contains("37.10"));
+    assert!(range.contains("38.1"));
+    assert!(!range.contains("35.2"));
+    assert!(!range.contains("39.0"));
+    assert!("37.0-".parse::<Versions>().unwrap().contains("40.0"));
+    assert_eq!(
+        "38".parse::<Versions>(),
+        Err(OverlayError::InvalidRange("38".into()))
+    );
+}
+
+#[test]
+fn apply_test() {
+    let spec: OpenAPI = serde_json::from_str(include_str!("codegen/test_spec.json")).unwrap();
+    let paths = spec.paths.paths.keys().cloned().collect::<Vec<_>>();
+    let correction = |versions: &str, target: &str| Correction {
+        name: "fix.json".into(),
+        versions: versions.parse().unwrap(),
+        document: Document::parse(
+            &json!({"overlay": "1.0.0", "actions": [
+                {"target": target, "update": {"description": "Corrected."}}
+            ]})
+            .to_string(),
+        )
+        .unwrap(),
+    };
+
+    let corrected = apply(
+        spec.clone(),
+        &[
+            correction("36.0-", "$.components.schemas.vcloud_VAppType"),
+            correction("-35.2", "$.nowhere"),
+        ],
+    )
+    .unwrap();
+    let schema = corrected.components.as_ref().unwrap().schemas["vcloud_VAppType"]
+        .as_item()
+        .unwrap();
+    assert_eq!(
+        schema.schema_data.description.as_deref(),
+        Some("Corrected.")
+    );
+    assert_eq!(
+        corrected.paths.paths.keys().cloned().collect::<Vec<_>>(),
+        paths
+    );
+
+    let error = apply(spec, &[correction("36.0-", "$.nowhere")]).unwrap_err();
+    assert_eq!(
+        format!("{:#}", error),
+        "Unable to apply fix.json to version 36.0: Nothing matches `$.nowhere`"
+    );
+}
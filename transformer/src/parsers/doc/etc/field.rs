@@ -17,10 +17,24 @@ pub(super) enum Occurrences {
     Array,
 }
 
+/// Where a field's value is found in the XML.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(super) enum FieldKind {
+    Attribute,
+    Element,
+    /// The text content of a type extending a simple type.
+    Content,
+}
+
 #[derive(Debug, PartialEq)]
 pub(super) struct Field {
     pub(super) annotation: Option<Annotation>,
     pub(super) name: String,
+    /// The element or attribute name as written in XML.
+    pub(super) xml_name: String,
+    /// The schema prefix of an element's namespace. Attributes are unqualified.
+    pub(super) namespace: Option<String>,
+    pub(super) kind: FieldKind,
     pub(super) r#type: openapiv3::ReferenceOr<SimpleType>,
     pub(super) occurrences: Occurrences,
 }
@@ -64,7 +78,7 @@ impl
                 match attributes
                     .get("ref")
                     .and_then(|type_name| {
-                        types.iter().find_map(|(_, xml)| {
+                        types.iter().find_map(|(ref_ns, xml)| {
                             xml.as_element()
                                 .and_then(|e| {
                                     e.children.iter().find(|&child| match child {
@@ -106,7 +120,7 @@ impl
                                             .or(attributes.get("type").map(|type_name| {
                                                 str_to_simple_type_or_reference(ns, type_name, None)
                                             }))
-                                            .map(|r#type| (name, r#type)),
+                                            .map(|r#type| (name, r#type, *ref_ns)),
                                     _ => None,
                                 })
                         })
@@ -119,8 +133,8 @@ impl
                         .or(attributes
                             .get("type")
                             .map(|type_name| str_to_simple_type_or_reference(ns, type_name, None)))
-                        .and_then(|r#type| attributes.get("name").map(|name| (name, r#type))))
-                    .and_then(|(name, ref r#type)| {
+                        .and_then(|r#type| attributes.get("name").map(|name| (name, r#type, ns))))
+                    .and_then(|(name, ref r#type, namespace)| {
                         children
                             .iter()
                             .flat_map(Annotation::try_from)
@@ -130,6 +144,9 @@ impl
                                 _ => Ok(Field {
                                     annotation: Some(annotation),
                                     name: decapitalize(name),
+                                    xml_name: name.to_owned(),
+                                    namespace: namespace.map(String::from),
+                                    kind: FieldKind::Element,
                                     r#type: r#type.to_owned(),
                                     occurrences: get_occurrences(xml),
                                 }),
@@ -137,6 +154,9 @@ impl
                             .or(Some(Ok(Field {
                                 annotation: None,
                                 name: decapitalize(name),
+                                xml_name: name.to_owned(),
+                                namespace: namespace.map(String::from),
+                                kind: FieldKind::Element,
                                 r#type: r#type.to_owned(),
                                 occurrences: get_occurrences(xml),
                             })))
@@ -152,11 +172,11 @@ impl
                 children,
                 ..
             }) if namespace == XML_SCHEMA_NS && name == "attribute" => {
-                let name = attributes
+                let xml_name = attributes
                     .get("name")
-                    .map(|name| decapitalize(name))
                     .ok_or(FieldParseError::MissingName)?
                     .to_owned();
+                let name = decapitalize(&xml_name);
                 let r#type = match children
                     .iter()
                     .flat_map(|xml| SimpleType::try_from((ns, xml)))
@@ -177,6 +197,9 @@ impl
                 Ok(Field {
                     annotation,
                     name,
+                    xml_name,
+                    namespace: None,
+                    kind: FieldKind::Attribute,
                     r#type,
                     occurrences,
                 })
@@ -261,7 +284,7 @@ impl From<&Field> for openapiv3::ReferenceOr<openapiv3::Schema> {
 }
 
 /// Decapitalizes the first character in s.
-fn decapitalize(s: &str) -> String {
+pub(crate) fn decapitalize(s: &str) -> String {
     let mut c = s.chars();
     match c.next() {
         None => String::new(),
@@ -0,0 +1,383 @@
+use crate::parsers::doc::etc::field::{Field, FieldKind, Occurrences};
+use crate::parsers::doc::etc::model::{discriminator_value, Model};
+use crate::parsers::doc::etc::r#type::Type;
+use crate::parsers::doc::etc::{namespace_prefix, namespace_uri, XML_SCHEMA_INSTANCE_NS};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JsonToXmlError {
+    #[error("no type named `{0}`")]
+    UnknownType(String),
+    #[error("no element is declared with type `{0}`")]
+    UnknownElement(String),
+    #[error("`{type_name}` has no property `{property}`")]
+    UnknownProperty { type_name: String, property: String },
+    #[error("`{property}` can't hold {value}")]
+    InvalidValue { property: String, value: Value },
+}
+
+/// An element ready to be written, its names already prefixed.
+#[derive(Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn write(&self, out: &mut String) {
+        out.push('<');
+        out.push_str(&self.name);
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(r#" {}="{}""#, name, escape(value)));
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        out.push_str(&escape(&self.text));
+        for child in &self.children {
+            child.write(out);
+        }
+        out.push_str(&format!("</{}>", self.name));
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The XML text of a simple value. Lists are space separated.
+fn to_text(property: &str, value: &Value) -> Result<String, JsonToXmlError> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| to_text(property, item))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|items| items.join(" ")),
+        _ => Err(JsonToXmlError::InvalidValue {
+            property: property.to_owned(),
+            value: value.clone(),
+        }),
+    }
+}
+
+struct Writer<'a> {
+    model: &'a Model,
+    /// Schema prefix of the root element's namespace, written as `xmlns`.
+    default_namespace: Option<&'a str>,
+    /// Other namespaces used, by prefix.
+    namespaces: BTreeMap<String, String>,
+}
+
+impl<'a> Writer<'a> {
+    /// Prefixes `name` unless it is in the default namespace or one the
+    /// registry doesn't know.
+    fn qualify(&mut self, namespace: Option<&str>, name: &str) -> String {
+        match namespace.and_then(|p| namespace_uri(p).map(|uri| (p, uri))) {
+            Some((prefix, _)) if Some(prefix) == self.default_namespace => name.to_owned(),
+            Some((prefix, uri)) => {
+                self.namespaces.insert(prefix.to_owned(), uri.to_owned());
+                format!("{}:{}", prefix, name)
+            }
+            None => name.to_owned(),
+        }
+    }
+
+    /// The prefix for an attribute namespace written as `{uri}` in `otherAttributes`.
+    fn attribute_prefix(&mut self, uri: &str) -> String {
+        if let Some(prefix) = self
+            .namespaces
+            .iter()
+            .find(|(_, u)| *u == uri)
+            .map(|(p, _)| p.clone())
+        {
+            return prefix;
+        }
+        let prefix = match namespace_prefix(uri) {
+            Some(prefix) => prefix.to_owned(),
+            None => (1..)
+                .map(|n| format!("ns{}", n))
+                .find(|p| !self.namespaces.contains_key(p))
+                .unwrap_or_default(),
+        };
+        self.namespaces.insert(prefix.clone(), uri.to_owned());
+        prefix
+    }
+
+    fn field_to_xml(&mut self, field: &Field, value: &Value) -> Result<XmlElement, JsonToXmlError> {
+        let name = self.qualify(field.namespace.as_deref(), &field.xml_name);
+        match &field.r#type {
+            openapiv3::ReferenceOr::Reference { reference } => {
+                self.element_to_xml(reference, name, &field.name, value)
+            }
+            openapiv3::ReferenceOr::Item(_) => Ok(XmlElement {
+                name,
+                text: to_text(&field.name, value)?,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn element_to_xml(
+        &mut self,
+        type_name: &str,
+        name: String,
+        property: &str,
+        value: &Value,
+    ) -> Result<XmlElement, JsonToXmlError> {
+        let mut element = XmlElement {
+            name,
+            ..Default::default()
+        };
+        match self.model.get(type_name) {
+            Some(Type::ObjectType(_)) => {}
+            Some(Type::SimpleType(_)) => {
+                element.text = to_text(property, value)?;
+                return Ok(element);
+            }
+            None => return Err(JsonToXmlError::UnknownType(type_name.to_owned())),
+        }
+        let object = value
+            .as_object()
+            .ok_or_else(|| JsonToXmlError::InvalidValue {
+                property: property.to_owned(),
+                value: value.clone(),
+            })?;
+
+        let mut type_name = type_name;
+        if let Some(discriminator) = object.get("_type") {
+            let discriminator = discriminator.as_str().unwrap_or_default();
+            if discriminator != discriminator_value(type_name) {
+                type_name = self
+                    .model
+                    .discriminated_type(type_name, discriminator)
+                    .ok_or_else(|| JsonToXmlError::UnknownType(discriminator.to_owned()))?;
+                self.namespaces
+                    .insert("xsi".into(), XML_SCHEMA_INSTANCE_NS.into());
+                let (namespace, local_name) = type_name
+                    .split_once('_')
+                    .map_or((None, type_name), |(ns, local_name)| (Some(ns), local_name));
+                let xsi_type = match namespace.and_then(namespace_uri) {
+                    Some(_) if namespace == self.default_namespace => local_name.to_owned(),
+                    Some(uri) => {
+                        let prefix = self.attribute_prefix(uri);
+                        format!("{}:{}", prefix, local_name)
+                    }
+                    None => local_name.to_owned(),
+                };
+                element.attributes.push(("xsi:type".into(), xsi_type));
+            }
+        }
+
+        let fields = self.model.fields(type_name);
+        let simple_content = self.model.simple_content(type_name).is_some();
+        for property in object.keys() {
+            let known = match property.as_str() {
+                "_type" | "otherAttributes" => true,
+                "value" if simple_content => true,
+                _ => fields.iter().any(|f| &f.name == property),
+            };
+            if !known {
+                return Err(JsonToXmlError::UnknownProperty {
+                    type_name: type_name.to_owned(),
+                    property: property.clone(),
+                });
+            }
+        }
+
+        for field in fields {
+            let value = match object.get(&field.name) {
+                None | Some(Value::Null) => continue,
+                Some(value) => value,
+            };
+            match field.kind {
+                FieldKind::Attribute => element
+                    .attributes
+                    .push((field.xml_name.clone(), to_text(&field.name, value)?)),
+                FieldKind::Content => element.text = to_text(&field.name, value)?,
+                FieldKind::Element => match (field.occurrences, value) {
+                    (Occurrences::Array, Value::Array(items)) => {
+                        for item in items {
+                            element.children.push(self.field_to_xml(field, item)?);
+                        }
+                    }
+                    _ => element.children.push(self.field_to_xml(field, value)?),
+                },
+            }
+        }
+        if simple_content {
+            if let Some(value) = object.get("value") {
+                element.text = to_text("value", value)?;
+            }
+        }
+
+        if let Some(other_attributes) = object.get("otherAttributes") {
+            let other_attributes =
+                other_attributes
+                    .as_object()
+                    .ok_or_else(|| JsonToXmlError::InvalidValue {
+                        property: "otherAttributes".into(),
+                        value: other_attributes.clone(),
+                    })?;
+            for (name, value) in other_attributes {
+                let name = match name.strip_prefix('{').and_then(|n| n.split_once('}')) {
+                    Some((uri, local_name)) => {
+                        format!("{}:{}", self.attribute_prefix(uri), local_name)
+                    }
+                    None => name.clone(),
+                };
+                element
+                    .attributes
+                    .push((name, to_text("otherAttributes", value)?));
+            }
+        }
+
+        Ok(element)
+    }
+}
+
+impl Model {
+    /// Serializes vCD's JSON representation of the schema `type_name` as
+    /// XML, the reverse of [`Model::xml_to_json`].
+    ///
+    /// Properties are written as attributes or elements as the XSDs declare
+    /// them, elements follow the sequence order and a `_type` naming a
+    /// subtype becomes `xsi:type`.
+    pub fn json_to_xml(&self, type_name: &str, json: &Value) -> Result<String, JsonToXmlError> {
+        let root = self
+            .element(type_name)
+            .ok_or_else(|| JsonToXmlError::UnknownElement(type_name.to_owned()))?;
+        let default_namespace = root
+            .namespace
+            .as_deref()
+            .filter(|p| namespace_uri(p).is_some());
+        let mut writer = Writer {
+            model: self,
+            default_namespace,
+            namespaces: BTreeMap::new(),
+        };
+        let name = writer.qualify(root.namespace.as_deref(), &root.name);
+        let mut element = writer.element_to_xml(type_name, name, &root.name, json)?;
+
+        let declarations = default_namespace
+            .and_then(namespace_uri)
+            .map(|uri| ("xmlns".to_owned(), uri.to_owned()))
+            .into_iter()
+            .chain(
+                writer
+                    .namespaces
+                    .into_iter()
+                    .map(|(prefix, uri)| (format!("xmlns:{}", prefix), uri)),
+            )
+            .collect::<Vec<_>>();
+        element.attributes.splice(0..0, declarations);
+
+        let mut out = String::new();
+        element.write(&mut out);
+        Ok(out)
+    }
+}
+
+#[test]
+fn json_to_xml_test() {
+    let model = Model::test_model(
+        Some("vcloud"),
+        &[include_bytes!("test_base.xsd"), include_bytes!("test.xsd")],
+    );
+    let value = json!({
+        "requiredAttribute": "a",
+        "otherAttributes": {
+            "{urn:other}extra": "x"
+        },
+        "baseField": "base",
+        "requiredString": "required & more",
+        "booleanField": true,
+        "signedThirtyTwo": -32,
+        "boundedCustom2": {
+            "someField": "foo"
+        },
+        "unboundedCustom2": [
+            {
+                "someField2": "bar"
+            },
+            {
+                "baseField": "baz"
+            }
+        ]
+    });
+    let xml = model.json_to_xml("vcloud_TestType", &value).unwrap();
+    assert_eq!(
+        xml,
+        concat!(
+            r#"<Test xmlns="http://www.vmware.com/vcloud/v1.5" xmlns:ns1="urn:other" requiredAttribute="a" ns1:extra="x">"#,
+            "<BaseField>base</BaseField>",
+            "<RequiredString>required &amp; more</RequiredString>",
+            "<BooleanField>true</BooleanField>",
+            "<SignedThirtyTwo>-32</SignedThirtyTwo>",
+            "<BoundedCustom2><SomeField>foo</SomeField></BoundedCustom2>",
+            "<UnboundedCustom2><SomeField2>bar</SomeField2></UnboundedCustom2>",
+            "<UnboundedCustom2><BaseField>baz</BaseField></UnboundedCustom2>",
+            "</Test>"
+        )
+    );
+    assert_eq!(model.xml_to_json("vcloud_TestType", &xml).unwrap(), value);
+}
+
+#[test]
+fn json_to_xml_type_and_value_test() {
+    let model = Model::test_model(Some("vcloud"), &[include_bytes!("test_query.xsd")]);
+    let value = json!({
+        "record": [
+            {
+                "_type": "QueryResultVMRecordType",
+                "href": "https://vcloud.example.com/api/vApp/vm-1",
+                "memoryMB": 2048
+            },
+            {
+                "_type": "QueryResultRecordType",
+                "href": "https://vcloud.example.com/api/vApp/vm-2"
+            }
+        ],
+        "quota": {
+            "unit": "GB",
+            "value": 10
+        }
+    });
+    let xml = model.json_to_xml("vcloud_RecordsType", &value).unwrap();
+    assert_eq!(
+        xml,
+        concat!(
+            r#"<Records xmlns="http://www.vmware.com/vcloud/v1.5" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+            r#"<Record xsi:type="QueryResultVMRecordType" href="https://vcloud.example.com/api/vApp/vm-1" memoryMB="2048"/>"#,
+            r#"<Record href="https://vcloud.example.com/api/vApp/vm-2"/>"#,
+            r#"<Quota unit="GB">10</Quota>"#,
+            "</Records>"
+        )
+    );
+    assert_eq!(
+        model.xml_to_json("vcloud_RecordsType", &xml).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn json_to_xml_unknown_property_test() {
+    let model = Model::test_model(Some("vcloud"), &[include_bytes!("test_query.xsd")]);
+    assert!(matches!(
+        model.json_to_xml("vcloud_RecordsType", &json!({"unknown": 1})),
+        Err(JsonToXmlError::UnknownProperty { property, .. }) if property == "unknown"
+    ));
+}
@@ -1,10 +1,38 @@
 const XML_SCHEMA_NS: &str = "http://www.w3.org/2001/XMLSchema";
+const XML_SCHEMA_INSTANCE_NS: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// Target namespaces of the XSDs and the prefixes their schema names get.
+const NAMESPACES: [(&str, &str); 10] = [
+    ("http://schemas.dmtf.org/ovf/envelope/1", "ovf"),
+    ("http://schemas.dmtf.org/ovf/environment/1", "ovfenv"),
+    ("http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_ResourceAllocationSettingData", "rasd"),
+    ("http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_VirtualSystemSettingData", "vssd"),
+    ("http://schemas.dmtf.org/wbem/wscim/1/common", "cim"),
+    ("http://www.vmware.com/vcloud/meta", "meta"),
+    ("http://www.vmware.com/schema/ovf", "vmw"),
+    ("http://www.vmware.com/vcloud/extension/v1.5", "vcloud-ext"),
+    ("http://www.vmware.com/vcloud/v1.5", "vcloud"),
+    ("http://www.vmware.com/vcloud/versions", "versioning"),
+];
+
+pub fn namespace_prefix(uri: &str) -> Option<&'static str> {
+    NAMESPACES.iter().find(|(u, _)| *u == uri).map(|(_, p)| *p)
+}
+
+pub fn namespace_uri(prefix: &str) -> Option<&'static str> {
+    NAMESPACES.iter().find(|(_, p)| *p == prefix).map(|(u, _)| *u)
+}
 
 mod annotation;
 mod field;
 mod group_ref;
+pub mod json_to_xml;
+pub mod model;
 mod object_type;
 mod primitive_type;
 pub mod schema;
 mod simple_type;
 mod r#type;
+pub mod rust_types;
+pub mod xml_to_json;
+
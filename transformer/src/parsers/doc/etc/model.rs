@@ -0,0 +1,211 @@
+use crate::parsers::doc::etc::field::Field;
+use crate::parsers::doc::etc::r#type::Type;
+use crate::parsers::doc::etc::schema::{Element, Schema};
+use crate::parsers::doc::etc::simple_type::SimpleType;
+use regex::Regex;
+use std::collections::BTreeMap;
+#[cfg(test)]
+use std::convert::TryFrom;
+
+/// Every type declared by the XSDs, looked up by schema name (`vcloud_VAppType`).
+#[derive(Debug, Default)]
+pub struct Model {
+    types: BTreeMap<String, Type>,
+    /// The first top level element declared for each type.
+    elements: BTreeMap<String, Element>,
+}
+
+impl Model {
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.types.contains_key(type_name)
+    }
+
+    pub(super) fn types(&self) -> impl Iterator<Item = (&String, &Type)> {
+        self.types.iter()
+    }
+
+    pub(super) fn get(&self, type_name: &str) -> Option<&Type> {
+        self.types.get(type_name)
+    }
+
+    pub(super) fn element(&self, type_name: &str) -> Option<&Element> {
+        self.elements.get(type_name)
+    }
+
+    /// The schema name a `_type` discriminator value stands for, preferring
+    /// types in the same namespace as `type_name`.
+    pub(super) fn discriminated_type(&self, type_name: &str, value: &str) -> Option<&str> {
+        let mut candidates = self
+            .types
+            .keys()
+            .filter(|name| discriminator_value(name) == value);
+        let first = candidates.next()?;
+        let ns = type_name.split_once('_').map(|(ns, _)| ns);
+        Some(
+            std::iter::once(first)
+                .chain(candidates)
+                .find(|name| name.split_once('_').map(|(ns, _)| ns) == ns)
+                .unwrap_or(first),
+        )
+    }
+
+    /// The fields of an object type including inherited ones, which come first
+    /// as they do in the XML.
+    pub(super) fn fields(&self, type_name: &str) -> Vec<&Field> {
+        match self.types.get(type_name) {
+            Some(Type::ObjectType(o)) => o
+                .parents
+                .iter()
+                .filter_map(|p| match p {
+                    openapiv3::ReferenceOr::Reference { reference } => Some(reference),
+                    openapiv3::ReferenceOr::Item(_) => None,
+                })
+                .flat_map(|p| self.fields(p))
+                .chain(o.fields.iter())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The named simple type an object type's text content takes, if it
+    /// extends one.
+    pub(super) fn simple_content(&self, type_name: &str) -> Option<&SimpleType> {
+        match self.types.get(type_name) {
+            Some(Type::SimpleType(s)) => Some(s),
+            Some(Type::ObjectType(o)) => o.parents.iter().find_map(|p| match p {
+                openapiv3::ReferenceOr::Reference { reference } => self.simple_content(reference),
+                openapiv3::ReferenceOr::Item(_) => None,
+            }),
+            None => None,
+        }
+    }
+
+    /// Whether a type is `vcloud_ReferenceType` or extends it.
+    fn is_reference_type(&self, type_name: &str) -> bool {
+        type_name == "vcloud_ReferenceType"
+            || match self.types.get(type_name) {
+                Some(Type::ObjectType(o)) => o.parents.iter().any(|p| match p {
+                    openapiv3::ReferenceOr::Reference { reference } => {
+                        self.is_reference_type(reference)
+                    }
+                    openapiv3::ReferenceOr::Item(_) => false,
+                }),
+                _ => false,
+            }
+    }
+
+    /// The reference-typed properties whose annotation names the media type
+    /// they point to, as `(type, property, target schema)`.
+    ///
+    /// The media type is taken from the field's `meta:content-type`, or else
+    /// from its documentation.
+    pub fn reference_targets(
+        &self,
+        content_type_mapping: &BTreeMap<String, String>,
+    ) -> Vec<(String, String, String)> {
+        lazy_static! {
+            static ref MEDIA_TYPE: Regex =
+                Regex::new(r"application/vnd\.vmware\.[\w.]+\+(?:xml|json)").unwrap();
+        }
+        self.types
+            .iter()
+            .filter_map(|(name, t)| match t {
+                Type::ObjectType(o) => Some((name, o)),
+                Type::SimpleType(_) => None,
+            })
+            .flat_map(|(name, o)| {
+                o.fields.iter().filter_map(move |field| {
+                    match &field.r#type {
+                        openapiv3::ReferenceOr::Reference { reference }
+                            if self.is_reference_type(reference) => {}
+                        _ => return None,
+                    }
+                    let annotation = field.annotation.as_ref()?;
+                    let media_type = annotation.content_type.clone().or_else(|| {
+                        let description = annotation.description.as_deref()?;
+                        Some(MEDIA_TYPE.find(description)?.as_str().to_string())
+                    })?;
+                    let media_type = media_type
+                        .trim_end_matches("+xml")
+                        .trim_end_matches("+json");
+                    let target = content_type_mapping.get(media_type)?;
+                    Some((name.clone(), field.name.clone(), target.clone()))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Model {
+    /// A model of the given XSDs, all in the namespace `ns`.
+    pub(crate) fn test_model(ns: Option<&str>, xsds: &[&[u8]]) -> Self {
+        let types = xsds
+            .iter()
+            .map(|xsd| {
+                (
+                    ns,
+                    xmltree::XMLNode::Element(xmltree::Element::parse(*xsd).unwrap()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut model = Model::default();
+        model.extend(
+            types
+                .iter()
+                .map(|(ns, xml)| Schema::try_from((*ns, xml, &types)).unwrap()),
+        );
+        model
+    }
+}
+
+impl Extend<Schema> for Model {
+    fn extend<I: IntoIterator<Item = Schema>>(&mut self, schemas: I) {
+        for schema in schemas {
+            for t in schema.types {
+                if let Some(name) = t.name().map(String::from) {
+                    self.types.entry(name).or_insert(t);
+                }
+            }
+            for e in schema.elements {
+                self.elements.entry(e.type_name.clone()).or_insert(e);
+            }
+        }
+    }
+}
+
+/// The `_type` discriminator value of a schema, as used in the discriminator mapping.
+pub fn discriminator_value(type_name: &str) -> String {
+    type_name
+        .split_once('_')
+        .map_or(type_name, |(_, local_name)| local_name)
+        .replace('_', "")
+}
+
+#[test]
+fn reference_targets_test() {
+    let model = Model::test_model(Some("vcloud"), &[include_bytes!("test_references.xsd")]);
+    let mapping = [
+        ("application/vnd.vmware.vcloud.vdc", "vcloud_VdcType"),
+        ("application/vnd.vmware.vcloud.org", "vcloud_OrgType"),
+    ]
+    .iter()
+    .map(|(m, t)| (m.to_string(), t.to_string()))
+    .collect();
+    assert!(model.is_reference_type("vcloud_LinkType"));
+    assert_eq!(
+        model.reference_targets(&mapping),
+        vec![
+            (
+                "vcloud_VAppType".to_string(),
+                "vdc".to_string(),
+                "vcloud_VdcType".to_string()
+            ),
+            (
+                "vcloud_VAppType".to_string(),
+                "org".to_string(),
+                "vcloud_OrgType".to_string()
+            ),
+        ]
+    );
+}
@@ -1,12 +1,13 @@
 #[cfg(test)]
 use super::r#type::Type;
 use super::{
-    field::Occurrences,
+    field::{FieldKind, Occurrences},
     simple_type::{str_to_simple_type_or_reference, SimpleType},
 };
 use crate::parsers::doc::etc::annotation::Annotation;
 use crate::parsers::doc::etc::field::Field;
 use crate::parsers::doc::etc::group_ref::GroupRef;
+use crate::parsers::doc::etc::model::discriminator_value;
 use crate::parsers::doc::etc::r#type::TypeParseError;
 use crate::parsers::doc::etc::XML_SCHEMA_NS;
 use openapiv3::Discriminator;
@@ -292,6 +293,9 @@ impl
                             removed: false,
                         }),
                         name: name.as_ref().map_or("value".into(), |s| s.into()),
+                        xml_name: String::new(),
+                        namespace: None,
+                        kind: FieldKind::Content,
                         occurrences: Occurrences::One,
                         r#type: openapiv3::ReferenceOr::Item(i.clone()),
                     }),
@@ -458,7 +462,7 @@ impl From<&ObjectType> for openapiv3::Schema {
                             .iter()
                             .map(|type_name| match type_name.split_once(':') {
                                 Some((ns, name)) => (
-                                    name.replace("_", "").to_owned(),
+                                    discriminator_value(&format!("{ns}_{name}")),
                                     format!("#/components/schemas/{ns}_{name}"),
                                 ),
                                 None => (
@@ -0,0 +1,357 @@
+//! Rust types generated straight from the XSDs, reading both `+xml` and
+//! `+json` bodies.
+//!
+//! Each field is known by its JSON name and, through quick-xml's naming, by
+//! its XML attribute (`@name`), element or text content (`$text`). Fields
+//! come in XML sequence order, inherited ones first and attributes before
+//! elements. `xsi:type` polymorphism isn't modelled: a base type only reads
+//! its own fields.
+
+use crate::codegen::rust::{
+    lib_rs, pascal_case, snake_case, unique_in, Field as RustField, Item, RustType,
+};
+use crate::parsers::doc::etc::field::{Field, FieldKind, Occurrences};
+use crate::parsers::doc::etc::model::Model;
+use crate::parsers::doc::etc::namespace_uri;
+use crate::parsers::doc::etc::primitive_type::PrimitiveType;
+use crate::parsers::doc::etc::r#type::Type;
+use crate::parsers::doc::etc::simple_type::SimpleType;
+#[cfg(test)]
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+const QUICK_XML: &str = r#"quick-xml = { version = "0.31", features = ["serialize"], optional = true }
+
+[features]
+xml = ["quick-xml"]
+"#;
+
+/// Helpers for reading and writing top level elements, appended to `lib.rs`.
+const XML_HELPERS: &str = r#"
+/// A type vCD sends as a top level element.
+pub trait Element {
+    const ELEMENT: &'static str;
+    const NAMESPACE: &'static str;
+}
+
+/// Reads an XML document. Element names are matched without their prefix.
+#[cfg(feature = "xml")]
+pub fn from_xml<T: serde::de::DeserializeOwned>(xml: &str) -> Result<T, quick_xml::DeError> {
+    use quick_xml::events::{BytesEnd, BytesStart, Event};
+    // quick-xml only collects repeated elements into a `Vec` if they are
+    // written the same way, so prefixes are dropped first.
+    fn local_name(name: quick_xml::name::LocalName) -> String {
+        String::from_utf8_lossy(name.as_ref()).into_owned()
+    }
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    loop {
+        let event = match reader.read_event()? {
+            Event::Eof => break,
+            Event::Start(e) => Event::Start(
+                BytesStart::new(local_name(e.local_name())).with_attributes(e.attributes().flatten()),
+            ),
+            Event::Empty(e) => Event::Empty(
+                BytesStart::new(local_name(e.local_name())).with_attributes(e.attributes().flatten()),
+            ),
+            Event::End(e) => Event::End(BytesEnd::new(local_name(e.local_name()))),
+            event => event,
+        };
+        writer.write_event(event)?;
+    }
+    let xml = writer.into_inner();
+    T::deserialize(&mut quick_xml::de::Deserializer::from_reader(xml.as_slice()))
+}
+
+/// Writes `value` as its top level element in its namespace.
+#[cfg(feature = "xml")]
+pub fn to_xml<T: Serialize + Element>(value: &T) -> Result<String, quick_xml::DeError> {
+    let mut xml = quick_xml::se::to_string_with_root(T::ELEMENT, value)?;
+    xml.insert_str(
+        T::ELEMENT.len() + 1,
+        &format!(" xmlns=\"{}\"", T::NAMESPACE),
+    );
+    Ok(xml)
+}
+"#;
+
+fn primitive(primitive: PrimitiveType) -> &'static str {
+    match primitive {
+        PrimitiveType::Boolean => "bool",
+        PrimitiveType::Byte
+        | PrimitiveType::UnsignedByte
+        | PrimitiveType::Short
+        | PrimitiveType::UnsignedShort
+        | PrimitiveType::Int => "i32",
+        PrimitiveType::Integer
+        | PrimitiveType::Long
+        | PrimitiveType::UnsignedInt
+        | PrimitiveType::UnsignedLong => "i64",
+        PrimitiveType::Double | PrimitiveType::Float => "f64",
+        _ => "String",
+    }
+}
+
+fn simple_type(simple_type: &SimpleType) -> RustType {
+    let r#type = RustType::Primitive(primitive(simple_type.parent));
+    if simple_type.list {
+        RustType::Vec(Box::new(r#type))
+    } else {
+        r#type
+    }
+}
+
+fn enumeration(simple_type: &SimpleType) -> Option<Vec<String>> {
+    let values = simple_type
+        .enumeration
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    if values.is_empty() || simple_type.list || simple_type.parent != PrimitiveType::String {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+impl Model {
+    /// The files of a crate with a type for every XSD type, by path.
+    pub fn rust_types(&self, title: &str, crate_name: &str) -> BTreeMap<&'static str, String> {
+        let mut names = self.types().map(|(n, _)| pascal_case(n)).collect();
+        let mut items = Vec::new();
+        let mut elements = Vec::new();
+        for (type_name, t) in self.types() {
+            let name = pascal_case(type_name);
+            match t {
+                Type::ObjectType(o) => {
+                    let fields = self.rust_fields(type_name, &name, &mut names, &mut items);
+                    items.push(Item::Struct {
+                        name: name.clone(),
+                        description: o.annotation.as_ref().and_then(|a| a.description.clone()),
+                        fields,
+                    });
+                    if let Some(element) = self.element(type_name) {
+                        let namespace = element.namespace.as_deref().and_then(namespace_uri);
+                        elements.push((name, element.name.clone(), namespace.unwrap_or("")));
+                    }
+                }
+                Type::SimpleType(s) => {
+                    let description = s.annotation.as_ref().and_then(|a| a.description.clone());
+                    items.push(match enumeration(s) {
+                        Some(values) => Item::StringEnum {
+                            name,
+                            description,
+                            values,
+                        },
+                        None => Item::Alias {
+                            name,
+                            description,
+                            r#type: simple_type(s),
+                        },
+                    });
+                }
+            }
+        }
+
+        let mut lib = lib_rs(title, &mut items);
+        lib.push_str(XML_HELPERS);
+        for (name, element, namespace) in elements {
+            write!(
+                lib,
+                "\nimpl Element for {} {{\n    const ELEMENT: &'static str = {:?};\n    const NAMESPACE: &'static str = {:?};\n}}\n",
+                name, element, namespace
+            )
+            .unwrap();
+        }
+        let cargo_toml = crate::codegen::cargo_toml(
+            crate_name,
+            &format!("XML and JSON models for {}", title),
+            QUICK_XML,
+        );
+        let mut files = BTreeMap::new();
+        files.insert("Cargo.toml", cargo_toml);
+        files.insert("src/lib.rs", lib);
+        files
+    }
+
+    /// The fields of an object type, attributes first, generating enums for
+    /// fields restricted to inline enumerations.
+    fn rust_fields(
+        &self,
+        type_name: &str,
+        owner: &str,
+        names: &mut BTreeSet<String>,
+        items: &mut Vec<Item>,
+    ) -> Vec<RustField> {
+        let mut fields = self.fields(type_name);
+        fields.sort_by_key(|f| f.kind != FieldKind::Attribute);
+        let content = if fields.iter().any(|f| f.kind == FieldKind::Content) {
+            None
+        } else {
+            self.simple_content(type_name).map(|s| RustField {
+                name: "value".into(),
+                json_name: "value".into(),
+                xml_name: Some("$text".into()),
+                description: None,
+                r#type: self.named_type(s),
+                required: true,
+            })
+        };
+        let mut idents = BTreeSet::new();
+        fields
+            .into_iter()
+            .map(|field| {
+                let ident = snake_case(&field.name);
+                let ident = (1..)
+                    .map(|i| match i {
+                        1 => ident.clone(),
+                        i => format!("{}_{}", ident.trim_start_matches("r#"), i),
+                    })
+                    .find(|i| idents.insert(i.clone()))
+                    .unwrap();
+                let r#type = self.field_type(field, owner, names, items);
+                RustField {
+                    name: ident,
+                    json_name: field.name.clone(),
+                    xml_name: Some(match field.kind {
+                        FieldKind::Attribute => format!("@{}", field.xml_name),
+                        FieldKind::Element => field.xml_name.clone(),
+                        FieldKind::Content => "$text".into(),
+                    }),
+                    description: field
+                        .annotation
+                        .as_ref()
+                        .and_then(|a| a.description.clone()),
+                    r#type: match field.occurrences {
+                        Occurrences::Array => RustType::Vec(Box::new(r#type)),
+                        _ => r#type,
+                    },
+                    required: field.occurrences != Occurrences::Array
+                        && field
+                            .annotation
+                            .as_ref()
+                            .and_then(|a| a.required)
+                            .unwrap_or(false),
+                }
+            })
+            .chain(content)
+            .collect()
+    }
+
+    fn field_type(
+        &self,
+        field: &Field,
+        owner: &str,
+        names: &mut BTreeSet<String>,
+        items: &mut Vec<Item>,
+    ) -> RustType {
+        match &field.r#type {
+            openapiv3::ReferenceOr::Reference { reference } if self.contains(reference) => {
+                RustType::Named(pascal_case(reference))
+            }
+            openapiv3::ReferenceOr::Reference { .. } => RustType::Value,
+            openapiv3::ReferenceOr::Item(s) => match enumeration(s) {
+                Some(values) => {
+                    let name = unique_in(names, format!("{}{}", owner, pascal_case(&field.name)));
+                    items.push(Item::StringEnum {
+                        name: name.clone(),
+                        description: None,
+                        values,
+                    });
+                    RustType::Named(name)
+                }
+                None => simple_type(s),
+            },
+        }
+    }
+
+    /// The type of a simple type's values, by name if it has one.
+    fn named_type(&self, s: &SimpleType) -> RustType {
+        match &s.name {
+            Some(name) if self.contains(name) => RustType::Named(pascal_case(name)),
+            _ => simple_type(s),
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_model() -> Model {
+    Model::test_model(
+        Some("vcloud"),
+        &[
+            include_bytes!("test_query.xsd"),
+            include_bytes!("test_references.xsd"),
+        ],
+    )
+}
+
+#[test]
+fn rust_types_test() {
+    let files = test_model().rust_types("Test API 36.0", "test-models");
+    let lib = &files["src/lib.rs"];
+    // Inherited attributes come first, then elements in sequence order.
+    assert!(lib.contains(
+        "pub struct VcloudLinkType {
+    #[serde(rename(deserialize = \"href\"), alias = \"@href\")]
+    #[cfg_attr(feature = \"xml\", serde(rename(serialize = \"@href\")))]
+    #[cfg_attr(not(feature = \"xml\"), serde(rename(serialize = \"href\")))]
+    #[serde(default, skip_serializing_if = \"Option::is_none\")]
+    pub href: Option<String>,"
+    ));
+    assert!(lib.contains(
+        "    #[serde(rename(deserialize = \"link\"), alias = \"Link\")]
+    #[cfg_attr(feature = \"xml\", serde(rename(serialize = \"Link\")))]
+    #[cfg_attr(not(feature = \"xml\"), serde(rename(serialize = \"link\")))]
+    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]
+    pub link: Vec<VcloudLinkType>,"
+    ));
+    // Simple content is the text of the element.
+    assert!(lib.contains(
+        "    #[serde(rename(deserialize = \"value\"), alias = \"$text\")]
+    #[cfg_attr(feature = \"xml\", serde(rename(serialize = \"$text\")))]
+    #[cfg_attr(not(feature = \"xml\"), serde(rename(serialize = \"value\")))]
+    pub value: i32,"
+    ));
+    assert!(lib.contains(
+        "impl Element for VcloudRecordsType {
+    const ELEMENT: &'static str = \"Records\";
+    const NAMESPACE: &'static str = \"http://www.vmware.com/vcloud/v1.5\";
+}"
+    ));
+    assert!(files["Cargo.toml"].contains("xml = [\"quick-xml\"]"));
+}
+
+#[test]
+#[ignore = "builds a generated crate, fetching its dependencies"]
+fn rust_types_compile_test() {
+    let files = test_model().rust_types("Test API 36.0", "test-models");
+    // A program reading the same records from XML and JSON.
+    let source = format!(
+        "use test_models::VcloudRecordsType;
+
+fn main() {{
+    let from_xml: VcloudRecordsType = test_models::from_xml({:?}).unwrap();
+    let from_json: VcloudRecordsType = serde_json::from_str({:?}).unwrap();
+    assert_eq!(from_xml, from_json);
+    assert_eq!(from_xml.quota.as_ref().unwrap().value, 10);
+    let xml = test_models::to_xml(&from_xml).unwrap();
+    assert_eq!(test_models::from_xml::<VcloudRecordsType>(&xml).unwrap(), from_json);
+}}",
+        r#"<Records xmlns="http://www.vmware.com/vcloud/v1.5" xmlns:v="http://www.vmware.com/vcloud/v1.5">
+                <Record href="https://example.com/api/vm/1"/>
+                <v:Record href="https://example.com/api/vm/2"/>
+                <Quota unit="GB">10</Quota>
+            </Records>"#,
+        json!({
+            "record": [
+                {"href": "https://example.com/api/vm/1"},
+                {"href": "https://example.com/api/vm/2"}
+            ],
+            "quota": {"unit": "GB", "value": 10}
+        })
+        .to_string()
+    );
+    crate::codegen::run_example(&files, "xml_and_json", &source, &["xml"], &[]);
+}
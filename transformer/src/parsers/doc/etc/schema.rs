@@ -1,4 +1,5 @@
 use crate::parsers::doc::etc::r#type::Type;
+use crate::parsers::doc::etc::simple_type::str_to_simple_type_or_reference;
 use crate::parsers::doc::etc::XML_SCHEMA_NS;
 #[cfg(test)]
 use serde_json::json;
@@ -8,7 +9,16 @@ use thiserror::Error;
 
 #[derive(Debug, PartialEq)]
 pub struct Schema {
-    types: Vec<Type>,
+    pub(super) types: Vec<Type>,
+    pub(super) elements: Vec<Element>,
+}
+
+/// A top level element declaration, such as `<xs:element name="VApp" type="VAppType"/>`.
+#[derive(Debug, PartialEq)]
+pub(super) struct Element {
+    pub(super) namespace: Option<String>,
+    pub(super) name: String,
+    pub(super) type_name: String,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -63,6 +73,34 @@ impl
                     .iter()
                     .flat_map(|x| Type::try_from((ns, x, types)))
                     .collect(),
+                elements: children
+                    .iter()
+                    .filter_map(|x| match x {
+                        xmltree::XMLNode::Element(xmltree::Element {
+                            namespace: Some(namespace),
+                            name,
+                            attributes,
+                            ..
+                        }) if namespace == XML_SCHEMA_NS && name == "element" => {
+                            match (attributes.get("name"), attributes.get("type")) {
+                                (Some(name), Some(type_name)) => {
+                                    match str_to_simple_type_or_reference(ns, type_name, None) {
+                                        openapiv3::ReferenceOr::Reference { reference } => {
+                                            Some(Element {
+                                                namespace: ns.map(String::from),
+                                                name: name.clone(),
+                                                type_name: reference,
+                                            })
+                                        }
+                                        openapiv3::ReferenceOr::Item(_) => None,
+                                    }
+                                }
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    })
+                    .collect(),
             }),
             _ => Err(SchemaParseError::NotSchemaNode),
         }
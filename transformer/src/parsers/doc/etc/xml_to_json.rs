@@ -0,0 +1,343 @@
+use crate::parsers::doc::etc::field::{decapitalize, Field, FieldKind, Occurrences};
+use crate::parsers::doc::etc::model::{discriminator_value, Model};
+use crate::parsers::doc::etc::primitive_type::PrimitiveType;
+use crate::parsers::doc::etc::r#type::Type;
+use crate::parsers::doc::etc::simple_type::SimpleType;
+use crate::parsers::doc::etc::{namespace_prefix, XML_SCHEMA_INSTANCE_NS};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::{Map, Value};
+use thiserror::Error;
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::Namespace;
+use xml::reader::{EventReader, XmlEvent};
+
+#[derive(Error, Debug)]
+pub enum XmlToJsonError {
+    #[error("XML parse error")]
+    XmlParse(#[from] xml::reader::Error),
+    #[error("document has no root element")]
+    NoRootElement,
+    #[error("no type named `{0}`")]
+    UnknownType(String),
+    #[error("`{type_name}` has no element `{element}`")]
+    UnknownElement { type_name: String, element: String },
+    #[error("`{value}` is not a valid {primitive:?}")]
+    InvalidValue {
+        value: String,
+        primitive: PrimitiveType,
+    },
+}
+
+/// An element with the qualified names of its attributes. xmltree keys
+/// attributes by local name, which would mix `xsi:type` up with `type`.
+struct Node {
+    name: OwnedName,
+    attributes: Vec<OwnedAttribute>,
+    namespace: Namespace,
+    children: Vec<Node>,
+    text: String,
+}
+
+fn parse(xml: &str) -> Result<Node, XmlToJsonError> {
+    let mut stack: Vec<Node> = Vec::new();
+    for event in EventReader::from_str(xml) {
+        match event? {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => stack.push(Node {
+                name,
+                attributes,
+                namespace,
+                children: Vec::new(),
+                text: String::new(),
+            }),
+            XmlEvent::EndElement { .. } => {
+                let node = stack.pop().ok_or(XmlToJsonError::NoRootElement)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(node),
+                }
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(XmlToJsonError::NoRootElement)
+}
+
+/// The schema name an `xsi:type` attribute selects, if the element has one.
+fn xsi_type(node: &Node) -> Option<String> {
+    node.attributes
+        .iter()
+        .find(|a| {
+            a.name.namespace.as_deref() == Some(XML_SCHEMA_INSTANCE_NS)
+                && a.name.local_name == "type"
+        })
+        .map(|a| {
+            let (prefix, local_name) = a.value.split_once(':').unwrap_or(("", &a.value));
+            match node.namespace.get(prefix).and_then(namespace_prefix) {
+                Some(ns) => format!("{}_{}", ns, local_name),
+                None => a.value.replacen(':', "_", 1),
+            }
+        })
+}
+
+fn find_field<'a>(fields: &[&'a Field], kind: FieldKind, local_name: &str) -> Option<&'a Field> {
+    let name = decapitalize(local_name);
+    fields
+        .iter()
+        .find(|f| f.kind == kind && f.name == name)
+        .copied()
+}
+
+fn primitive_to_json(primitive: PrimitiveType, text: &str) -> Result<Value, XmlToJsonError> {
+    let invalid = || XmlToJsonError::InvalidValue {
+        value: text.to_owned(),
+        primitive,
+    };
+    match primitive {
+        PrimitiveType::Boolean => match text.trim() {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(invalid()),
+        },
+        PrimitiveType::Int
+        | PrimitiveType::Integer
+        | PrimitiveType::Long
+        | PrimitiveType::Short
+        | PrimitiveType::UnsignedInt
+        | PrimitiveType::UnsignedLong
+        | PrimitiveType::UnsignedShort => text
+            .trim()
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.trim().parse::<u64>().map(Value::from))
+            .map_err(|_| invalid()),
+        PrimitiveType::Double | PrimitiveType::Float => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(invalid),
+        _ => Ok(Value::String(text.to_owned())),
+    }
+}
+
+fn simple_to_json(simple_type: &SimpleType, text: &str) -> Result<Value, XmlToJsonError> {
+    if simple_type.list {
+        text.split_whitespace()
+            .map(|item| primitive_to_json(simple_type.parent, item))
+            .collect::<Result<_, _>>()
+            .map(Value::Array)
+    } else {
+        primitive_to_json(simple_type.parent, text)
+    }
+}
+
+impl Model {
+    /// Converts an XML document into vCD's JSON representation, reading the
+    /// root element as the schema `type_name`.
+    ///
+    /// Names are decapitalized, `xsi:type` becomes `_type`, text content
+    /// becomes `value`, repeatable elements become arrays and attributes
+    /// the XSDs don't declare are kept in `otherAttributes`.
+    pub fn xml_to_json(&self, type_name: &str, xml: &str) -> Result<Value, XmlToJsonError> {
+        self.element_to_json(type_name, &parse(xml)?)
+    }
+
+    fn simple_field_to_json(&self, field: &Field, text: &str) -> Result<Value, XmlToJsonError> {
+        match &field.r#type {
+            openapiv3::ReferenceOr::Item(s) => simple_to_json(s, text),
+            openapiv3::ReferenceOr::Reference { reference } => match self.get(reference) {
+                Some(Type::SimpleType(s)) => simple_to_json(s, text),
+                _ => Err(XmlToJsonError::UnknownType(reference.clone())),
+            },
+        }
+    }
+
+    fn element_to_json(&self, type_name: &str, node: &Node) -> Result<Value, XmlToJsonError> {
+        let xsi_type = xsi_type(node);
+        let type_name = xsi_type.as_deref().unwrap_or(type_name);
+        let object_type = match self.get(type_name) {
+            Some(Type::ObjectType(o)) => o,
+            Some(Type::SimpleType(s)) => return simple_to_json(s, &node.text),
+            None => return Err(XmlToJsonError::UnknownType(type_name.to_owned())),
+        };
+        let fields = self.fields(type_name);
+        let mut object = Map::new();
+
+        if xsi_type.is_some() || !object_type.descendants.is_empty() {
+            object.insert("_type".into(), discriminator_value(type_name).into());
+        }
+
+        let mut other_attributes = Map::new();
+        for attribute in &node.attributes {
+            let local_name = &attribute.name.local_name;
+            match attribute.name.namespace.as_deref() {
+                Some(XML_SCHEMA_INSTANCE_NS) => {}
+                Some(namespace) => {
+                    other_attributes.insert(
+                        format!("{{{}}}{}", namespace, local_name),
+                        attribute.value.clone().into(),
+                    );
+                }
+                None => match find_field(&fields, FieldKind::Attribute, local_name) {
+                    Some(field) => {
+                        object.insert(
+                            field.name.clone(),
+                            self.simple_field_to_json(field, &attribute.value)?,
+                        );
+                    }
+                    None => {
+                        other_attributes.insert(local_name.clone(), attribute.value.clone().into());
+                    }
+                },
+            }
+        }
+        if !other_attributes.is_empty() {
+            object.insert("otherAttributes".into(), Value::Object(other_attributes));
+        }
+
+        for child in &node.children {
+            let field = find_field(&fields, FieldKind::Element, &child.name.local_name)
+                .ok_or_else(|| XmlToJsonError::UnknownElement {
+                    type_name: type_name.to_owned(),
+                    element: child.name.local_name.clone(),
+                })?;
+            let value = match &field.r#type {
+                openapiv3::ReferenceOr::Item(s) => simple_to_json(s, &child.text)?,
+                openapiv3::ReferenceOr::Reference { reference } => {
+                    self.element_to_json(reference, child)?
+                }
+            };
+            match field.occurrences {
+                Occurrences::Array => {
+                    if let Value::Array(values) = object
+                        .entry(field.name.clone())
+                        .or_insert_with(|| Value::Array(Vec::new()))
+                    {
+                        values.push(value);
+                    }
+                }
+                _ => {
+                    object.insert(field.name.clone(), value);
+                }
+            }
+        }
+
+        let text = node.text.trim();
+        if !text.is_empty() {
+            match fields.iter().find(|f| f.kind == FieldKind::Content) {
+                Some(field) => {
+                    object.insert(field.name.clone(), self.simple_field_to_json(field, text)?);
+                }
+                None => {
+                    if let Some(s) = self.simple_content(type_name) {
+                        object.insert("value".into(), simple_to_json(s, text)?);
+                    }
+                }
+            }
+        }
+
+        Ok(Value::Object(object))
+    }
+}
+
+#[test]
+fn xml_to_json_test() {
+    let xml = r#"
+    <Test xmlns="http://www.vmware.com/vcloud/v1.5" xmlns:other="urn:other" requiredAttribute="a" other:extra="x">
+        <BaseField>base</BaseField>
+        <RequiredString>required</RequiredString>
+        <BooleanField>true</BooleanField>
+        <SignedThirtyTwo>-32</SignedThirtyTwo>
+        <BoundedCustom2>
+            <SomeField>foo</SomeField>
+        </BoundedCustom2>
+        <UnboundedCustom2>
+            <SomeField2>bar</SomeField2>
+        </UnboundedCustom2>
+        <UnboundedCustom2>
+            <BaseField>baz</BaseField>
+        </UnboundedCustom2>
+    </Test>"#;
+    assert_eq!(
+        Model::test_model(
+            Some("test"),
+            &[include_bytes!("test_base.xsd"), include_bytes!("test.xsd")]
+        )
+        .xml_to_json("test_TestType", xml)
+        .unwrap(),
+        json!({
+            "requiredAttribute": "a",
+            "otherAttributes": {
+                "{urn:other}extra": "x"
+            },
+            "baseField": "base",
+            "requiredString": "required",
+            "booleanField": true,
+            "signedThirtyTwo": -32,
+            "boundedCustom2": {
+                "someField": "foo"
+            },
+            "unboundedCustom2": [
+                {
+                    "someField2": "bar"
+                },
+                {
+                    "baseField": "baz"
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn xml_to_json_unknown_element_test() {
+    let xml = r#"<Test xmlns="http://www.vmware.com/vcloud/v1.5"><Unknown/></Test>"#;
+    assert!(matches!(
+        Model::test_model(Some("test"), &[include_bytes!("test_base.xsd"), include_bytes!("test.xsd")])
+            .xml_to_json("test_TestType", xml),
+        Err(XmlToJsonError::UnknownElement { element, .. }) if element == "Unknown"
+    ));
+}
+
+#[test]
+fn xml_to_json_type_and_value_test() {
+    let model = Model::test_model(Some("vcloud"), &[include_bytes!("test_query.xsd")]);
+    let xml = r#"
+    <Records xmlns="http://www.vmware.com/vcloud/v1.5" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+        <Record xsi:type="QueryResultVMRecordType" href="https://vcloud.example.com/api/vApp/vm-1" memoryMB="2048"/>
+        <Record href="https://vcloud.example.com/api/vApp/vm-2"/>
+        <Quota unit="GB">10</Quota>
+    </Records>"#;
+    assert_eq!(
+        model.xml_to_json("vcloud_RecordsType", xml).unwrap(),
+        json!({
+            "record": [
+                {
+                    "_type": "QueryResultVMRecordType",
+                    "href": "https://vcloud.example.com/api/vApp/vm-1",
+                    "memoryMB": 2048
+                },
+                {
+                    "_type": "QueryResultRecordType",
+                    "href": "https://vcloud.example.com/api/vApp/vm-2"
+                }
+            ],
+            "quota": {
+                "unit": "GB",
+                "value": 10
+            }
+        })
+    );
+}
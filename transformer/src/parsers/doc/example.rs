@@ -0,0 +1,35 @@
+/// A request or response body shown in an operation page's examples.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Example {
+    /// Media type of the example without the `+xml` suffix, if it names one.
+    pub media_type: Option<String>,
+    pub xml: String,
+}
+
+/// The XML body inside an example, as plain text.
+///
+/// Bodies that aren't well formed XML are discarded.
+pub fn get_body_example(text: &str) -> Option<String> {
+    scraper::Html::parse_fragment(text)
+        .select(&scraper::Selector::parse("#response-body-div, #request-body-div").unwrap())
+        .next()
+        .map(|el| {
+            el.text()
+                .collect::<String>()
+                .replace('\u{a0}', " ")
+                .trim()
+                .to_string()
+        })
+        .filter(|xml| !xml.is_empty() && xmltree::Element::parse(xml.as_bytes()).is_ok())
+}
+
+#[test]
+fn get_body_example_test() {
+    let html = r#"<strong>Body:</strong><br>
+        <div id="response-body-div" class="xml"><span class="xml_tag_symbols">&lt;</span><span class="xml_tag_name">Test</span>&nbsp;<span class="xml_attr_name">name</span>=<span class="xml_attr_value">"a&nbsp;b"</span><span class="xml_tag_symbols">/&gt;</span></div>"#;
+    assert_eq!(
+        get_body_example(html),
+        Some(r#"<Test name="a b"/>"#.to_string())
+    );
+    assert_eq!(get_body_example("<strong>Body:</strong><br>"), None);
+}
@@ -1,6 +1,7 @@
 pub mod common_res;
 pub mod detail_page;
 pub mod etc;
+pub mod example;
 pub mod operation;
 pub mod query;
 pub mod r#type;
@@ -1,21 +1,44 @@
 use super::detail_page::{DefinitionList, DefinitionListValue, DetailPage, DetailPageFromStrError};
+use super::etc::model::Model;
+use super::example::{get_body_example, Example};
+use crate::responses::ERROR_RESPONSE;
+use crate::schema_tweaks::header_parameters::{
+    header_parameter, header_parameter_name, is_ignored_header, well_known_header_parameter_name,
+    AUTH_CONTEXT, CLIENT_REQUEST_ID, TENANT_CONTEXT,
+};
+use crate::schema_tweaks::response_headers;
 
 use indexmap::map::IndexMap;
 use indexmap::set::IndexSet;
 
 use regex::Regex;
-#[cfg(test)]
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::{convert::TryFrom, str::FromStr};
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+/// Schema of the body returned by operations that complete asynchronously.
+pub const TASK_SCHEMA: &str = "vcloud_TaskType";
+
+/// Extension marking operations that return a task to be polled.
+pub const ASYNC_TASK_EXTENSION: &str = "x-vcloud-async-task";
+/// The XML an example was documented with, kept on the JSON media types it was
+/// converted for, since the definition only has JSON media types.
+pub const XML_EXAMPLE_EXTENSION: &str = "x-vcloud-xml-example";
+
+/// Path of the operation that reports a task's progress.
+const TASK_STATUS_PATH: &str = "/task/{id}";
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub enum Method {
     Get,
     Post,
     Put,
     Delete,
+    Patch,
+    Head,
+    Options,
+    Trace,
 }
 
 #[derive(Error, Debug)]
@@ -33,29 +56,55 @@ impl FromStr for Method {
             "POST" => Ok(Self::Post),
             "PUT" => Ok(Self::Put),
             "DELETE" => Ok(Self::Delete),
+            "PATCH" => Ok(Self::Patch),
+            "HEAD" => Ok(Self::Head),
+            "OPTIONS" => Ok(Self::Options),
+            "TRACE" => Ok(Self::Trace),
             other => Err(Self::Err::UnknownMethodError(other.into())),
         }
     }
 }
 
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Delete => "DELETE",
+            Self::Patch => "PATCH",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
-pub struct QueryParameter {
+pub struct Parameter {
     pub name: String,
     pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Operation {
+    pub name: String,
     pub method: Method,
     pub path: String,
     pub description: String,
     pub tag: &'static str,
     pub request_contents: IndexSet<(String, String)>,
+    pub request_headers: Vec<(String, String)>,
+    pub request_example: Option<Example>,
     pub response_description: Option<String>,
     pub response_contents: IndexSet<(String, String)>,
+    pub response_status: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    pub response_example: Option<Example>,
     pub basic_auth: bool,
     pub deprecated: bool,
-    pub query_parameters: Vec<QueryParameter>,
+    pub path_parameters: Vec<Parameter>,
+    pub query_parameters: Vec<Parameter>,
 }
 
 #[derive(Error, Debug)]
@@ -96,6 +145,43 @@ fn get_content_media_type(text: &str) -> Option<String> {
         .and_then(|c| c.get(0).map(|m| m.as_str().to_string()));
 }
 
+fn get_response_status(text: &str) -> Option<u16> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(Status:|HTTP(&nbsp;|\s)1\.[01])(&nbsp;|\s)*(?P<status>[1-5]\d\d)")
+                .unwrap();
+    }
+    RE.captures(text)
+        .and_then(|c| c.name("status"))
+        .and_then(|m| m.as_str().parse().ok())
+}
+
+/// The `(name, value)` pairs listed under "Headers:" in an example.
+fn get_headers(text: &str) -> Vec<(String, String)> {
+    text.split_once("<strong>Headers:</strong>")
+        .map(|(_, headers)| {
+            headers
+                .split("<strong>Body:</strong>")
+                .next()
+                .unwrap_or_default()
+                .split("<br>")
+                .map(|line| {
+                    scraper::Html::parse_fragment(line)
+                        .root_element()
+                        .text()
+                        .collect::<String>()
+                        .replace('\u{a0}', " ")
+                })
+                .filter_map(|line| {
+                    line.split_once(':')
+                        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                })
+                .filter(|(name, _)| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn get_content_element(text: &str) -> Option<String> {
     scraper::Html::parse_fragment(text)
         .select(
@@ -129,6 +215,49 @@ fn get_content_media_types_from_examples(
         })
 }
 
+fn get_parameters(definition_list: &DefinitionList, definition_key: &str) -> Vec<Parameter> {
+    definition_list
+        .find(definition_key)
+        .and_then(DefinitionListValue::as_sublist)
+        .map(|s| {
+            s.0.iter()
+                .filter_map(|(key, value)| value.as_text().map(|v| (key, v)))
+                .fold((None, Vec::new()), |(name, mut acc), (key, value)| {
+                    match (key.as_str(), name) {
+                        ("Parameter", _) => (Some(value.to_string()), acc),
+                        ("Documentation", Some(name)) => {
+                            let description = if value.is_empty() {
+                                None
+                            } else {
+                                Some(html2md::parse_html(value))
+                            };
+                            acc.push(Parameter { name, description });
+                            (None, acc)
+                        }
+                        (_, name) => (name, acc),
+                    }
+                })
+                .1
+        })
+        .unwrap_or_else(Vec::new)
+}
+
+fn get_example(definition_list: &DefinitionList, definition_key: &str) -> Option<Example> {
+    definition_list
+        .find("Examples")
+        .and_then(DefinitionListValue::as_sublist)
+        .and_then(|d| d.find(definition_key))
+        .and_then(DefinitionListValue::as_text)
+        .and_then(|text| {
+            get_body_example(text).map(|xml| Example {
+                media_type: get_content_media_type(text)
+                    .filter(|m| !m.contains('*'))
+                    .map(|m| m.trim_end_matches("+xml").trim_end_matches("+json").into()),
+                xml,
+            })
+        })
+}
+
 fn merge_mimes(
     first_mimes: &IndexSet<String>,
     second_mimes: &IndexSet<(String, String)>,
@@ -175,6 +304,13 @@ impl TryFrom<DetailPage> for Operation {
             .find("Description:")
             .and_then(DefinitionListValue::text_to_markdown)
             .ok_or(Self::Error::CannotFindDescriptionError)?;
+        let name = p
+            .title
+            .rsplit(" - ")
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
         let tag = if path.starts_with("/admin/extension") {
             "extension"
         } else if path.starts_with("/admin") {
@@ -216,6 +352,36 @@ impl TryFrom<DetailPage> for Operation {
             })
             .unwrap_or_default();
 
+        let response_status = p
+            .definition_list
+            .find("Examples")
+            .and_then(DefinitionListValue::as_sublist)
+            .and_then(|d| d.find("Response"))
+            .and_then(DefinitionListValue::as_text)
+            .and_then(get_response_status);
+
+        let response_headers = p
+            .definition_list
+            .find("Examples")
+            .and_then(DefinitionListValue::as_sublist)
+            .and_then(|d| d.find("Response"))
+            .and_then(DefinitionListValue::as_text)
+            .map(get_headers)
+            .unwrap_or_default();
+
+        let request_example = get_example(&p.definition_list, "Request");
+
+        let response_example = get_example(&p.definition_list, "Response");
+
+        let request_headers = p
+            .definition_list
+            .find("Examples")
+            .and_then(DefinitionListValue::as_sublist)
+            .and_then(|d| d.find("Request"))
+            .and_then(DefinitionListValue::as_text)
+            .map(get_headers)
+            .unwrap_or_default();
+
         let basic_auth = p
             .definition_list
             .find("Examples")
@@ -227,42 +393,27 @@ impl TryFrom<DetailPage> for Operation {
 
         let deprecated = p.definition_list.filter("Deprecated:").any(|_| true);
 
-        let query_parameters = p
-            .definition_list
-            .find("Query parameters")
-            .and_then(DefinitionListValue::as_sublist)
-            .map(|s| {
-                s.0.iter()
-                    .filter_map(|(key, value)| value.as_text().map(|v| (key, v)))
-                    .fold((None, Vec::new()), |(name, mut acc), (key, value)| {
-                        match (key.as_str(), name) {
-                            ("Parameter", _) => (Some(value.to_string()), acc),
-                            ("Documentation", Some(name)) => {
-                                let description = if value.is_empty() {
-                                    None
-                                } else {
-                                    Some(html2md::parse_html(value))
-                                };
-                                acc.push(QueryParameter { name, description });
-                                (None, acc)
-                            }
-                            (_, name) => (name, acc),
-                        }
-                    })
-                    .1
-            })
-            .unwrap_or_else(Vec::new);
+        let path_parameters = get_parameters(&p.definition_list, "Path parameters");
+
+        let query_parameters = get_parameters(&p.definition_list, "Query parameters");
 
         Ok(Self {
+            name,
             method,
             path,
             description,
             tag,
             request_contents,
+            request_headers,
+            request_example,
             response_description,
             response_contents,
+            response_status,
+            response_headers,
+            response_example,
             basic_auth,
             deprecated,
+            path_parameters,
             query_parameters,
         })
     }
@@ -316,12 +467,65 @@ fn content_media_types_to_content(
         .collect()
 }
 
+/// Attaches an example, converted to JSON by its schema, to the JSON media
+/// types it documents, along with the XML it was documented with.
+///
+/// Examples whose schema the XSDs don't declare, or that don't match it, are
+/// left out rather than documented with the wrong shape; their XML is kept.
+fn add_example(
+    content: &mut IndexMap<String, openapiv3::MediaType>,
+    example: &Example,
+    model: &Model,
+) {
+    let mut keys = content
+        .keys()
+        .filter(|key| {
+            example
+                .media_type
+                .iter()
+                .all(|media_type| key.starts_with(&format!("{}+json;", media_type)))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if keys.is_empty() && content.len() == 1 {
+        keys.extend(content.keys().filter(|key| key.contains("json")).cloned());
+    }
+    for key in keys {
+        let media_type = &mut content[&key];
+        media_type
+            .extensions
+            .insert(XML_EXAMPLE_EXTENSION.into(), json!(example.xml));
+        let schema_name = match &media_type.schema {
+            Some(openapiv3::ReferenceOr::Reference { reference }) => {
+                reference.strip_prefix("#/components/schemas/")
+            }
+            _ => None,
+        };
+        let schema_name = match schema_name.filter(|name| model.contains(name)) {
+            Some(name) => name,
+            None => continue,
+        };
+        match model.xml_to_json(schema_name, &example.xml) {
+            Ok(value) => media_type.example = Some(value),
+            Err(e) => warn!("Skipping example for {} as it doesn't match its schema: {}", key, e),
+        }
+    }
+}
+
+fn references_schema(content: &IndexMap<String, openapiv3::MediaType>, schema: &str) -> bool {
+    let reference = format!("#/components/schemas/{}", schema);
+    content.values().any(|media_type| {
+        matches!(&media_type.schema, Some(openapiv3::ReferenceOr::Reference { reference: r }) if r == &reference)
+    })
+}
+
 impl Operation {
     pub fn to_openapi(
         self,
         api_version: &str,
         type_mapping: &BTreeMap<String, String>,
         element_mapping: &BTreeMap<String, String>,
+        model: &Model,
     ) -> openapiv3::Operation {
         let mut request_content =
             mimes_to_content(&self.request_contents, api_version, type_mapping);
@@ -339,19 +543,111 @@ impl Operation {
             element_mapping,
         ));
 
+        if let Some(example) = &self.request_example {
+            add_example(&mut request_content, example, model);
+        }
+        if let Some(example) = &self.response_example {
+            add_example(&mut response_content, example, model);
+        }
+
+        let is_async_task = references_schema(&response_content, TASK_SCHEMA);
+
+        let status = self.response_status.unwrap_or_else(|| {
+            if is_async_task {
+                202
+            } else if response_content.is_empty() {
+                204
+            } else {
+                200
+            }
+        });
+
+        let response_headers = self
+            .response_headers
+            .into_iter()
+            .filter(|(name, _)| !response_headers::is_ignored_header(name))
+            .map(
+                |(name, value)| match response_headers::well_known_header_name(&name) {
+                    Some(name) => (
+                        name.to_string(),
+                        openapiv3::ReferenceOr::Reference {
+                            reference: format!("#/components/headers/{}", name),
+                        },
+                    ),
+                    None => (
+                        name,
+                        openapiv3::ReferenceOr::Item(response_headers::response_header(
+                            None,
+                            Some(value),
+                        )),
+                    ),
+                },
+            )
+            .collect();
+
+        // A system administrator can act on behalf of an organization on any
+        // operation, not only the admin ones.
+        let mut header_parameters = [CLIENT_REQUEST_ID, TENANT_CONTEXT, AUTH_CONTEXT]
+            .into_iter()
+            .map(|name| {
+                (
+                    name.to_ascii_lowercase(),
+                    openapiv3::ReferenceOr::Reference {
+                        reference: format!(
+                            "#/components/parameters/{}",
+                            header_parameter_name(name)
+                        ),
+                    },
+                )
+            })
+            .collect::<IndexMap<_, _>>();
+        for (name, value) in self.request_headers {
+            if is_ignored_header(&name) {
+                continue;
+            }
+            header_parameters
+                .entry(name.to_ascii_lowercase())
+                .or_insert_with(|| match well_known_header_parameter_name(&name) {
+                    Some(reference) => openapiv3::ReferenceOr::Reference {
+                        reference: format!("#/components/parameters/{}", reference),
+                    },
+                    None => openapiv3::ReferenceOr::Item(header_parameter(
+                        &name,
+                        None,
+                        Default::default(),
+                        Some(value),
+                    )),
+                });
+        }
+
         openapiv3::Operation {
             description: Some(self.description),
             responses: openapiv3::Responses {
-                responses: [(
-                    openapiv3::StatusCode::Range(2),
-                    openapiv3::ReferenceOr::Item(openapiv3::Response {
-                        description: self
-                            .response_description
-                            .unwrap_or_else(|| "success".into()),
-                        content: response_content,
-                        ..Default::default()
-                    }),
-                )]
+                responses: [
+                    (
+                        openapiv3::StatusCode::Code(status),
+                        openapiv3::ReferenceOr::Item(openapiv3::Response {
+                            description: self
+                                .response_description
+                                .unwrap_or_else(|| "success".into()),
+                            headers: response_headers,
+                            content: response_content,
+                            ..Default::default()
+                        }),
+                    ),
+                    (
+                        openapiv3::StatusCode::Range(4),
+                        openapiv3::ReferenceOr::Reference {
+                            reference: format!("#/components/responses/{}", ERROR_RESPONSE),
+                        },
+                    ),
+                    (
+                        openapiv3::StatusCode::Range(5),
+                        openapiv3::ReferenceOr::Reference {
+                            reference: format!("#/components/responses/{}", ERROR_RESPONSE),
+                        },
+                    ),
+                ]
                 .iter()
                 .cloned()
                 .collect(),
@@ -397,7 +693,20 @@ impl Operation {
                         allow_empty_value: None,
                     })
                 })
+                .chain(header_parameters.into_values())
                 .collect(),
+            extensions: if is_async_task {
+                indexmap! {
+                    ASYNC_TASK_EXTENSION.into() => json!({
+                        "statusPath": TASK_STATUS_PATH,
+                        "statusProperty": "status",
+                        "success": ["success"],
+                        "failure": ["error", "aborted"]
+                    })
+                }
+            } else {
+                Default::default()
+            },
             ..Default::default()
         }
     }
@@ -409,6 +718,7 @@ fn parse_operation_test() {
     assert_eq!(
         actual,
         Operation {
+            name: "PUT-Test".into(),
             method: Method::Put,
             path: "/admin/test/{id}".into(),
             description: "Update a test.".into(),
@@ -417,19 +727,25 @@ fn parse_operation_test() {
                 ("application/vnd.vmware.admin.test+xml".into(), "".into()),
                 ("application/vnd.vmware.admin.test+json".into(), "".into())
             ],
+            request_headers: vec![],
+            request_example: None,
             response_description: Some("AdminTestType  \n\nExtended description".into()),
             response_contents: indexset![
                 ("application/vnd.vmware.admin.testo+xml".into(), "".into()),
                 ("application/vnd.vmware.admin.testo+json".into(), "".into())
             ],
+            response_status: None,
+            response_headers: vec![],
+            response_example: None,
             basic_auth: false,
             deprecated: false,
+            path_parameters: vec![],
             query_parameters: vec![
-                QueryParameter {
+                Parameter {
                     name: "force".into(),
                     description: Some("Documentation for force".into())
                 },
-                QueryParameter {
+                Parameter {
                     name: "recursive".into(),
                     description: None
                 }
@@ -438,6 +754,19 @@ fn parse_operation_test() {
     )
 }
 
+#[test]
+fn parse_path_parameters_test() {
+    let actual = Operation::try_from(include_str!("operations/GET-Test.html")).unwrap();
+    assert_eq!(actual.name, "GET-Test");
+    assert_eq!(
+        actual.path_parameters,
+        vec![Parameter {
+            name: "id".into(),
+            description: Some("The *id* of the test.".into())
+        }]
+    );
+}
+
 #[test]
 fn generate_schema_test() {
     let op = Operation::try_from(include_str!("operations/PUT-Test.html")).unwrap();
@@ -469,6 +798,7 @@ fn generate_schema_test() {
         .iter()
         .cloned()
         .collect(),
+        &Model::default(),
     );
     assert_eq!(
         serde_json::to_value(value).unwrap(),
@@ -494,6 +824,15 @@ fn generate_schema_test() {
                 "$ref": "#/components/schemas/query-parameter_recursive"
               },
               "style": "form"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-CLIENT-REQUEST-ID"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-TENANT-CONTEXT"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-AUTH-CONTEXT"
             }
           ],
           "requestBody": {
@@ -507,7 +846,7 @@ fn generate_schema_test() {
             "required": true
           },
           "responses": {
-            "2XX": {
+            "200": {
               "description": "AdminTestType  \n\nExtended description",
               "content": {
                 "application/vnd.vmware.admin.testo+json;version=32.0": {
@@ -516,6 +855,12 @@ fn generate_schema_test() {
                   }
                 }
               }
+            },
+            "4XX": {
+              "$ref": "#/components/responses/error"
+            },
+            "5XX": {
+              "$ref": "#/components/responses/error"
             }
           },
           "security": [
@@ -530,7 +875,12 @@ fn generate_schema_test() {
 #[test]
 fn generate_schema_test_for_basic_auth() {
     let op = Operation::try_from(include_str!("operations/POST-Login.html")).unwrap();
-    let value = op.to_openapi("32.0", &BTreeMap::new(), &BTreeMap::new());
+    let value = op.to_openapi(
+        "32.0",
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        &Model::default(),
+    );
 
     assert_eq!(
         serde_json::to_value(value).unwrap(),
@@ -539,9 +889,26 @@ fn generate_schema_test_for_basic_auth() {
             "user"
           ],
           "description": "Log in.",
+          "parameters": [
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-CLIENT-REQUEST-ID"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-TENANT-CONTEXT"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-AUTH-CONTEXT"
+            }
+          ],
           "responses": {
-            "2XX": {
+            "204": {
               "description": "success"
+            },
+            "4XX": {
+              "$ref": "#/components/responses/error"
+            },
+            "5XX": {
+              "$ref": "#/components/responses/error"
             }
           },
           "deprecated": true,
@@ -554,6 +921,214 @@ fn generate_schema_test_for_basic_auth() {
     )
 }
 
+#[test]
+fn generate_schema_test_for_task() {
+    let op = Operation::try_from(include_str!("operations/PUT-Test.html")).unwrap();
+    let value = op.to_openapi(
+        "32.0",
+        &[(
+            "application/vnd.vmware.admin.testo".to_string(),
+            TASK_SCHEMA.to_string(),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+        &BTreeMap::new(),
+        &Model::default(),
+    );
+
+    assert_eq!(
+        value.responses.responses.keys().collect::<Vec<_>>(),
+        vec![
+            &openapiv3::StatusCode::Code(202),
+            &openapiv3::StatusCode::Range(4),
+            &openapiv3::StatusCode::Range(5)
+        ]
+    );
+    assert_eq!(
+        value.extensions.get(ASYNC_TASK_EXTENSION),
+        Some(&json!({
+            "statusPath": "/task/{id}",
+            "statusProperty": "status",
+            "success": ["success"],
+            "failure": ["error", "aborted"]
+        }))
+    )
+}
+
+#[test]
+fn parse_method_test() {
+    for method in [
+        Method::Get,
+        Method::Post,
+        Method::Put,
+        Method::Delete,
+        Method::Patch,
+        Method::Head,
+        Method::Options,
+        Method::Trace,
+    ] {
+        assert_eq!(method.as_str().parse::<Method>().unwrap(), method);
+    }
+    assert!("CONNECT".parse::<Method>().is_err());
+}
+
+#[test]
+fn generate_header_parameters_test() {
+    let op = Operation::try_from(include_str!("operations/GET-Test.html")).unwrap();
+    assert_eq!(
+        op.request_headers,
+        vec![
+            (
+                "Accept".to_string(),
+                "application/*+xml;version=32.0".to_string()
+            ),
+            (
+                "x-vmware-vcloud-tenant-context".to_string(),
+                "a93c9db9-7471-3192-8d09-a8f7eeda85f9".to_string()
+            ),
+            ("X-Test-Trace".to_string(), "trace <1>".to_string()),
+        ]
+    );
+    let value = op.to_openapi(
+        "32.0",
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        &Model::default(),
+    );
+    assert_eq!(
+        serde_json::to_value(value.parameters).unwrap(),
+        json!([
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-CLIENT-REQUEST-ID"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-TENANT-CONTEXT"
+            },
+            {
+              "$ref": "#/components/parameters/header-parameter_X-VMWARE-VCLOUD-AUTH-CONTEXT"
+            },
+            {
+              "in": "header",
+              "name": "X-Test-Trace",
+              "schema": {
+                "type": "string"
+              },
+              "example": "trace <1>",
+              "style": "simple"
+            }
+        ])
+    );
+}
+
+#[test]
+fn generate_response_headers_test() {
+    let op = Operation::try_from(include_str!("operations/GET-Test.html")).unwrap();
+    assert_eq!(op.response_status, Some(200));
+    let value = op.to_openapi(
+        "32.0",
+        &BTreeMap::new(),
+        &BTreeMap::new(),
+        &Model::default(),
+    );
+    assert_eq!(
+        serde_json::to_value(&value.responses.responses[&openapiv3::StatusCode::Code(200)])
+            .unwrap(),
+        json!({
+            "description": "AdminTestType",
+            "headers": {
+                "X-VMWARE-VCLOUD-ACCESS-TOKEN": {
+                    "$ref": "#/components/headers/X-VMWARE-VCLOUD-ACCESS-TOKEN"
+                },
+                "X-VMWARE-VCLOUD-REQUEST-ID": {
+                    "schema": {
+                        "type": "string"
+                    },
+                    "example": "6c5c4e0b-2ea3-4a4e-9d93-0d5e1a7a1d40",
+                    "style": "simple"
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn generate_examples_test() {
+    let op = Operation::try_from(include_str!("operations/GET-Test.html")).unwrap();
+    let xml = r#"<AdminTest xmlns="http://www.vmware.com/vcloud/v1.5" name="test">
+    <Description>A test</Description>
+</AdminTest>"#;
+    assert_eq!(
+        op.response_example,
+        Some(Example {
+            media_type: Some("application/vnd.vmware.admin.test".into()),
+            xml: xml.into(),
+        })
+    );
+    let model = Model::test_model(
+        Some("vcloud"),
+        &[br#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns="http://www.vmware.com/vcloud/v1.5" targetNamespace="http://www.vmware.com/vcloud/v1.5">
+            <xs:complexType name="AdminTestType">
+                <xs:sequence>
+                    <xs:element name="Description" type="xs:string" minOccurs="0"/>
+                </xs:sequence>
+                <xs:attribute name="name" type="xs:string"/>
+            </xs:complexType>
+        </xs:schema>"#],
+    );
+    let content = |type_name: &str, model: &Model| {
+        let type_mapping = BTreeMap::from([(
+            "application/vnd.vmware.admin.test".to_string(),
+            type_name.to_string(),
+        )]);
+        let op = Operation::try_from(include_str!("operations/GET-Test.html")).unwrap();
+        let value = op.to_openapi("32.0", &type_mapping, &BTreeMap::new(), model);
+        serde_json::to_value(&value.responses.responses[&openapiv3::StatusCode::Code(200)])
+            .unwrap()["content"]
+            .clone()
+    };
+    assert_eq!(
+        content("vcloud_AdminTestType", &model),
+        json!({
+            "application/vnd.vmware.admin.test+json;version=32.0": {
+                "schema": {
+                    "$ref": "#/components/schemas/vcloud_AdminTestType"
+                },
+                "example": {
+                    "name": "test",
+                    "description": "A test"
+                },
+                "x-vcloud-xml-example": xml
+            }
+        })
+    );
+    // Without a schema to convert by, only the XML is kept.
+    assert_eq!(
+        content("MyType", &Model::default()),
+        json!({
+            "application/vnd.vmware.admin.test+json;version=32.0": {
+                "schema": {
+                    "$ref": "#/components/schemas/MyType"
+                },
+                "x-vcloud-xml-example": xml
+            }
+        })
+    );
+}
+
+#[test]
+fn capture_response_status() {
+    assert_eq!(
+        get_response_status("Status:&nbsp;201<br><br><strong>Headers:</strong>"),
+        Some(201)
+    );
+    assert_eq!(
+        get_response_status("HTTP&nbsp;1.1&nbsp;204&nbsp;No Content"),
+        Some(204)
+    );
+    assert_eq!(get_response_status("<strong>Body:</strong><br>"), None);
+}
+
 #[test]
 fn capture_content_multiple_media_types() {
     let actual = get_content_media_type("Content-Type:&nbsp;application/*+xml;version=5.5");
@@ -10,8 +10,26 @@ pub enum QueryParseError {
     CannotFindTypeName,
 }
 
+#[derive(Debug, PartialEq)]
 pub struct Query {
     pub name: String,
+    pub description: Option<String>,
+    /// Schema name of the records returned with `format=records`.
+    pub record_type: Option<String>,
+    /// Schema name of the references returned with `format=references`.
+    pub reference_type: Option<String>,
+    pub attributes: Vec<QueryAttribute>,
+}
+
+/// A queryable attribute of a query type's records.
+#[derive(Debug, PartialEq)]
+pub struct QueryAttribute {
+    pub name: String,
+    /// The type as documented, such as `String` or `DateTime`.
+    pub r#type: String,
+    pub filterable: bool,
+    pub sortable: bool,
+    pub description: Option<String>,
 }
 
 impl TryFrom<&str> for Query {
@@ -32,6 +50,118 @@ impl TryFrom<DetailPage> for Query {
             .and_then(DefinitionListValue::as_text)
             .ok_or(Self::Error::CannotFindTypeName)?
             .to_string();
-        Ok(Query { name })
+        let description = p
+            .definition_list
+            .find("Description:")
+            .and_then(DefinitionListValue::text_to_markdown)
+            .filter(|d| !d.is_empty());
+        let result_type = |key| {
+            p.definition_list
+                .find(key)
+                .and_then(DefinitionListValue::to_inner_text)
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .map(|t| format!("vcloud_{}", t))
+        };
+        let record_type = result_type("Record Result:");
+        let reference_type = result_type("Reference Result:");
+        let attributes = p
+            .definition_list
+            .find("Attributes:")
+            .and_then(DefinitionListValue::as_text)
+            .map(get_attributes)
+            .unwrap_or_default();
+        Ok(Query {
+            name,
+            description,
+            record_type,
+            reference_type,
+            attributes,
+        })
     }
 }
+
+/// Reads the attribute table, finding columns by their headings.
+fn get_attributes(html: &str) -> Vec<QueryAttribute> {
+    let fragment = scraper::Html::parse_fragment(html);
+    let row_selector = scraper::Selector::parse("tr").unwrap();
+    let cell_selector = scraper::Selector::parse("th, td").unwrap();
+    let mut rows = fragment.select(&row_selector).map(|row| {
+        row.select(&cell_selector)
+            .map(|cell| cell.text().collect::<String>().trim().to_string())
+            .collect::<Vec<_>>()
+    });
+    let headings = rows.next().unwrap_or_default();
+    let column = |heading: &str| {
+        headings
+            .iter()
+            .position(|h| h.to_ascii_lowercase().starts_with(heading))
+    };
+    let name = column("attribute").or_else(|| column("name"));
+    let r#type = column("type");
+    let filter = column("filter");
+    let sort = column("sort");
+    let description = column("description");
+    let flag = |cells: &[String], column: Option<usize>| {
+        let value = column
+            .and_then(|c| cells.get(c))
+            .map(|v| v.to_ascii_lowercase());
+        matches!(value.as_deref(), Some("yes" | "true" | "y"))
+    };
+    rows.filter_map(|cells| {
+        let name = name.and_then(|c| cells.get(c)).filter(|n| !n.is_empty())?;
+        Some(QueryAttribute {
+            name: name.clone(),
+            r#type: r#type
+                .and_then(|c| cells.get(c))
+                .cloned()
+                .unwrap_or_default(),
+            filterable: flag(&cells, filter),
+            sortable: flag(&cells, sort),
+            description: description
+                .and_then(|c| cells.get(c))
+                .filter(|d| !d.is_empty())
+                .cloned(),
+        })
+    })
+    .collect()
+}
+
+#[test]
+fn parse_query_test() {
+    let query = Query::try_from(include_str!("queries/adminVApp.html")).unwrap();
+    assert_eq!(query.name, "adminVApp");
+    assert_eq!(
+        query.description,
+        Some("All vApps in the system.".to_string())
+    );
+    assert_eq!(
+        query.record_type,
+        Some("vcloud_QueryResultAdminVAppRecordType".to_string())
+    );
+    assert_eq!(
+        query.reference_type,
+        Some("vcloud_ReferenceType".to_string())
+    );
+    assert_eq!(query.attributes.len(), 5);
+    assert_eq!(
+        query.attributes[0],
+        QueryAttribute {
+            name: "name".into(),
+            r#type: "String".into(),
+            filterable: true,
+            sortable: true,
+            description: Some("The name of this vApp.".into()),
+        }
+    );
+    assert_eq!(
+        query.attributes[2],
+        QueryAttribute {
+            name: "numberOfVMs".into(),
+            r#type: "Integer".into(),
+            filterable: false,
+            sortable: true,
+            description: None,
+        }
+    );
+}
@@ -1,6 +1,12 @@
-use crate::parsers::doc::operation::{Method, Operation};
+use crate::operation_ids::OperationIds;
+use crate::parsers::doc::etc::model::Model;
+use crate::parsers::doc::operation::{Method, Operation, OperationParseError};
+use crate::schema_tweaks::path_parameters::{
+    path_parameter, path_parameter_name, path_template_parameters,
+};
 use anyhow::{Context, Result};
-use openapiv3::Paths;
+use indexmap::IndexMap;
+use openapiv3::{Parameter, Paths, ReferenceOr};
 use std::collections::BTreeMap;
 use std::{
     convert::TryFrom,
@@ -13,9 +19,9 @@ pub fn paths<R: Read + Seek>(
     content_type_mapping: BTreeMap<String, String>,
     content_element_mapping: BTreeMap<String, String>,
     api_version: String,
+    parameters: &mut IndexMap<String, ReferenceOr<Parameter>>,
+    model: &Model,
 ) -> Result<Paths> {
-    let path_param_regex =
-        regex::Regex::new(r"\{([^}]+)}").context("Unable to create path param regex")?;
     let mut path_file_names = zip
         .file_names()
         .filter(|n| n.starts_with("doc/operations/"))
@@ -25,11 +31,7 @@ pub fn paths<R: Read + Seek>(
 
     path_file_names.sort();
 
-    let mut paths = Paths {
-        paths: Default::default(),
-        extensions: Default::default(),
-    };
-
+    let mut operations = Vec::new();
     for file_name in path_file_names {
         let mut html = String::new();
         zip.by_name(&file_name)
@@ -37,40 +39,55 @@ pub fn paths<R: Read + Seek>(
             .read_to_string(&mut html)
             .with_context(|| format!("Unable to read file {}", file_name))?;
 
-        let operation = Operation::try_from(html.as_str())
-            .with_context(|| format!("Unable to convert file to operation {}", file_name))?;
-        if !operation.path.starts_with('/') {
-            continue;
+        let operation = match Operation::try_from(html.as_str()) {
+            Err(OperationParseError::MethodParseError(e)) => {
+                warn!("Skipping {}: {}", file_name, e);
+                continue;
+            }
+            operation => operation
+                .with_context(|| format!("Unable to convert file to operation {}", file_name))?,
+        };
+        if operation.path.starts_with('/') {
+            operations.push(operation);
+        }
+    }
+
+    let mut operation_ids = OperationIds::new()?;
+    for operation in &operations {
+        operation_ids.register(operation.method, &operation.path, &operation.name);
+    }
+
+    let mut paths = Paths {
+        paths: Default::default(),
+        extensions: Default::default(),
+    };
+
+    for operation in operations {
+        for (segment, name) in path_template_parameters(&operation.path) {
+            let description = operation
+                .path_parameters
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.description.clone());
+            let parameter = parameters
+                .entry(path_parameter_name(segment, name))
+                .or_insert_with(|| ReferenceOr::Item(path_parameter(segment, name, None)));
+            if let ReferenceOr::Item(Parameter::Path { parameter_data, .. }) = parameter {
+                if parameter_data.description.is_none() {
+                    parameter_data.description = description;
+                }
+            }
         }
-        if let openapiv3::ReferenceOr::Item(path_item) =
+        if let ReferenceOr::Item(path_item) =
             paths.paths.entry(operation.path.clone()).or_insert_with(|| {
-                openapiv3::ReferenceOr::Item(openapiv3::PathItem {
-                    parameters: path_param_regex
-                        .captures_iter(&operation.path)
-                        .map(|c| {
-                            openapiv3::ReferenceOr::Item(openapiv3::Parameter::Path {
-                                parameter_data: openapiv3::ParameterData {
-                                    name: c[1].into(),
-                                    required: true,
-                                    description: None,
-                                    deprecated: None,
-                                    format: openapiv3::ParameterSchemaOrContent::Schema(
-                                        openapiv3::ReferenceOr::Item(openapiv3::Schema {
-                                            schema_data: Default::default(),
-                                            schema_kind: openapiv3::SchemaKind::Type(
-                                                openapiv3::Type::String(
-                                                    openapiv3::StringType::default(),
-                                                ),
-                                            ),
-                                        }),
-                                    ),
-                                    example: None,
-                                    examples: Default::default(),
-                                    explode: None,
-                                    extensions: Default::default(),
-                                },
-                                style: Default::default(),
-                            })
+                ReferenceOr::Item(openapiv3::PathItem {
+                    parameters: path_template_parameters(&operation.path)
+                        .into_iter()
+                        .map(|(segment, name)| ReferenceOr::Reference {
+                            reference: format!(
+                                "#/components/parameters/{}",
+                                path_parameter_name(segment, name)
+                            ),
                         })
                         .collect(),
                     ..Default::default()
@@ -78,16 +95,25 @@ pub fn paths<R: Read + Seek>(
             })
         {
             let method = operation.method;
-            let openapi_op = Some(operation.to_openapi(
-                &api_version,
-                &content_type_mapping,
-                &content_element_mapping,
-            ));
+            let operation_id = operation_ids.assign(method, &operation.path, &operation.name);
+            let openapi_op = Some(openapiv3::Operation {
+                operation_id: Some(operation_id),
+                ..operation.to_openapi(
+                    &api_version,
+                    &content_type_mapping,
+                    &content_element_mapping,
+                    model,
+                )
+            });
             match method {
                 Method::Get => path_item.get = openapi_op,
                 Method::Post => path_item.post = openapi_op,
                 Method::Put => path_item.put = openapi_op,
                 Method::Delete => path_item.delete = openapi_op,
+                Method::Patch => path_item.patch = openapi_op,
+                Method::Head => path_item.head = openapi_op,
+                Method::Options => path_item.options = openapi_op,
+                Method::Trace => path_item.trace = openapi_op,
             }
         };
     }
@@ -0,0 +1,22 @@
+use indexmap::IndexMap;
+use openapiv3::{MediaType, ReferenceOr, Response};
+
+/// Name of the shared response returned for any 4XX or 5XX status.
+pub const ERROR_RESPONSE: &str = "error";
+
+pub fn responses(api_version: &str) -> IndexMap<String, ReferenceOr<Response>> {
+    indexmap! {
+        ERROR_RESPONSE.into() => ReferenceOr::Item(Response {
+            description: "The request could not be completed.".into(),
+            content: indexmap! {
+                format!("application/vnd.vmware.vcloud.error+json;version={}", api_version) => MediaType {
+                    schema: Some(ReferenceOr::Reference {
+                        reference: "#/components/schemas/vcloud_ErrorType".into(),
+                    }),
+                    ..Default::default()
+                }
+            },
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use crate::parsers::doc::etc::model::discriminator_value;
+use crate::spec::SCHEMA_PREFIX;
+use indexmap::IndexMap;
+use openapiv3::{ReferenceOr, Schema};
+use serde_json::json;
+
+/// The discriminator value standing for a schema, as read by generators.
+pub const DISCRIMINATOR_VALUE_EXTENSION: &str = "x-discriminator-value";
+
+const DISCRIMINATOR: &str = "_type";
+
+/// Adjusts the `_type` discriminators for code generators.
+///
+/// Each mapping gets keys made the same way as the values vCloud Director
+/// sends and gains its base schema, and every schema it maps to is marked with
+/// its value. The clash of `_type` with `type` properties is not fixed here.
+pub fn codegen_discriminators(schemas: &mut IndexMap<String, ReferenceOr<Schema>>) {
+    let mut values = Vec::new();
+    for (name, schema) in schemas.iter_mut() {
+        let schema = match schema {
+            ReferenceOr::Item(schema) => schema,
+            ReferenceOr::Reference { .. } => continue,
+        };
+        let discriminator = match &mut schema.schema_data.discriminator {
+            Some(discriminator) if discriminator.property_name == DISCRIMINATOR => discriminator,
+            _ => continue,
+        };
+        let targets = std::iter::once(format!("{}{}", SCHEMA_PREFIX, name))
+            .chain(std::mem::take(&mut discriminator.mapping).into_values());
+        for target in targets {
+            let value = discriminator_value(target.trim_start_matches(SCHEMA_PREFIX));
+            if !discriminator.mapping.contains_key(&value) {
+                values.push((target.clone(), value.clone()));
+                discriminator.mapping.insert(value, target);
+            }
+        }
+    }
+    for (target, value) in values {
+        if let Some(ReferenceOr::Item(schema)) =
+            schemas.get_mut(target.trim_start_matches(SCHEMA_PREFIX))
+        {
+            schema
+                .schema_data
+                .extensions
+                .insert(DISCRIMINATOR_VALUE_EXTENSION.into(), json!(value));
+        }
+    }
+}
+
+#[test]
+fn codegen_discriminators_test() {
+    let mut schemas: IndexMap<String, ReferenceOr<Schema>> = serde_json::from_value(json!({
+        "ovf_Section_Type": {
+            "discriminator": {
+                "propertyName": "_type",
+                "mapping": {
+                    "ProductSection_Type": "#/components/schemas/ovf_ProductSection_Type",
+                    "NetworkConfigSectionType": "#/components/schemas/vcloud_NetworkConfigSectionType"
+                }
+            },
+            "type": "object",
+            "properties": {"_type": {"type": "string"}},
+            "required": ["_type"]
+        },
+        "ovf_ProductSection_Type": {
+            "allOf": [
+                {"$ref": "#/components/schemas/ovf_Section_Type"},
+                {"type": "object", "properties": {"type": {"type": "string"}}}
+            ]
+        },
+        "vcloud_NetworkConfigSectionType": {
+            "allOf": [{"$ref": "#/components/schemas/ovf_Section_Type"}]
+        },
+        "query-result-records": {
+            "discriminator": {"propertyName": "name", "mapping": {}},
+            "oneOf": []
+        }
+    }))
+    .unwrap();
+    let unchanged = schemas["query-result-records"].clone();
+
+    codegen_discriminators(&mut schemas);
+
+    assert_eq!(
+        serde_json::to_value(&schemas["ovf_Section_Type"]).unwrap(),
+        json!({
+            "discriminator": {
+                "propertyName": "_type",
+                "mapping": {
+                    "SectionType": "#/components/schemas/ovf_Section_Type",
+                    "ProductSectionType": "#/components/schemas/ovf_ProductSection_Type",
+                    "NetworkConfigSectionType": "#/components/schemas/vcloud_NetworkConfigSectionType"
+                }
+            },
+            "x-discriminator-value": "SectionType",
+            "type": "object",
+            "properties": {"_type": {"type": "string"}},
+            "required": ["_type"]
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&schemas["ovf_ProductSection_Type"]).unwrap()["x-discriminator-value"],
+        "ProductSectionType"
+    );
+    assert_eq!(schemas["query-result-records"], unchanged);
+}
@@ -0,0 +1,114 @@
+use indexmap::IndexMap;
+use openapiv3::{
+    HeaderStyle, Parameter, ParameterData, ParameterSchemaOrContent, ReferenceOr, Schema,
+    SchemaKind, StringType, Type, VariantOrUnknownOrEmpty,
+};
+
+pub const TENANT_CONTEXT: &str = "X-VMWARE-VCLOUD-TENANT-CONTEXT";
+pub const AUTH_CONTEXT: &str = "X-VMWARE-VCLOUD-AUTH-CONTEXT";
+pub const CLIENT_REQUEST_ID: &str = "X-VMWARE-VCLOUD-CLIENT-REQUEST-ID";
+
+/// Headers that OpenAPI describes elsewhere (content negotiation and security
+/// schemes) or that the HTTP client sets itself. OpenAPI ignores `Accept`
+/// parameters, so the `version` it negotiates is explained in the info's
+/// description instead.
+const IGNORED_HEADERS: [&str; 7] = [
+    "Accept",
+    "Authorization",
+    "Content-Length",
+    "Content-Type",
+    "Date",
+    "Host",
+    "x-vcloud-authorization",
+];
+
+pub fn header_parameter_name(name: &str) -> String {
+    format!("header-parameter_{}", name)
+}
+
+/// The reusable name of a header vCD understands on every call.
+pub fn well_known_header_parameter_name(name: &str) -> Option<String> {
+    [TENANT_CONTEXT, AUTH_CONTEXT, CLIENT_REQUEST_ID]
+        .iter()
+        .find(|h| h.eq_ignore_ascii_case(name))
+        .map(|h| header_parameter_name(h))
+}
+
+pub fn is_ignored_header(name: &str) -> bool {
+    IGNORED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name))
+}
+
+pub fn header_parameter(
+    name: &str,
+    description: Option<String>,
+    string_type: StringType,
+    example: Option<String>,
+) -> Parameter {
+    Parameter::Header {
+        parameter_data: ParameterData {
+            name: name.into(),
+            description,
+            required: false,
+            deprecated: None,
+            format: ParameterSchemaOrContent::Schema(ReferenceOr::Item(Schema {
+                schema_data: Default::default(),
+                schema_kind: SchemaKind::Type(Type::String(string_type)),
+            })),
+            example: example.map(serde_json::Value::String),
+            examples: Default::default(),
+            explode: None,
+            extensions: Default::default(),
+        },
+        style: HeaderStyle::Simple,
+    }
+}
+
+pub fn header_parameters() -> IndexMap<String, ReferenceOr<Parameter>> {
+    [
+        (
+            TENANT_CONTEXT,
+            "The id of the organization a system administrator is acting on behalf of.",
+            StringType {
+                format: VariantOrUnknownOrEmpty::Unknown("uuid".to_owned()),
+                ..Default::default()
+            },
+        ),
+        (
+            AUTH_CONTEXT,
+            "The name of the organization a system administrator is acting on behalf of.",
+            StringType::default(),
+        ),
+        (
+            CLIENT_REQUEST_ID,
+            "An identifier chosen by the client, echoed back on the task and in the logs.",
+            StringType {
+                max_length: Some(128),
+                ..Default::default()
+            },
+        ),
+    ]
+    .iter()
+    .map(|(name, description, string_type)| {
+        (
+            header_parameter_name(name),
+            ReferenceOr::Item(header_parameter(
+                name,
+                Some(description.to_string()),
+                string_type.clone(),
+                None,
+            )),
+        )
+    })
+    .collect()
+}
+
+#[test]
+fn well_known_header_parameter_name_test() {
+    assert_eq!(
+        well_known_header_parameter_name("x-vmware-vcloud-tenant-context"),
+        Some("header-parameter_X-VMWARE-VCLOUD-TENANT-CONTEXT".to_string())
+    );
+    assert_eq!(well_known_header_parameter_name("X-Custom"), None);
+    assert!(is_ignored_header("accept"));
+    assert!(!is_ignored_header("X-Custom"));
+}
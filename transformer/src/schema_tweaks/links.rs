@@ -0,0 +1,490 @@
+use crate::parsers::doc::operation::XML_EXAMPLE_EXTENSION;
+use crate::schema_tweaks::path_parameters::path_template_parameters;
+use crate::spec::{match_template, media_type_key, operations};
+use indexmap::IndexMap;
+use openapiv3::{
+    Link, LinkOperation, Operation, PathItem, Paths, ReferenceOr, Response, StatusCode,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Relations that read the resource they lead to.
+const GET_RELS: [&str; 10] = [
+    "alternate",
+    "down",
+    "entityResolver",
+    "firstPage",
+    "lastPage",
+    "nextPage",
+    "previousPage",
+    "recordsList",
+    "up",
+    "task:owner",
+];
+
+/// A `Link` element found in an example body.
+#[derive(Debug, PartialEq)]
+struct ExampleLink {
+    rel: String,
+    media_type: Option<String>,
+    href: String,
+    /// Whether the href is that of the entity the body describes, or below it.
+    own: bool,
+}
+
+/// Where a link leads, keyed by the name the OpenAPI link gets.
+#[derive(Debug, Clone)]
+struct LinkTarget {
+    rel: String,
+    media_type: Option<String>,
+    path: String,
+    operation_id: String,
+    own: bool,
+}
+
+/// The links of the entity an example body describes. Links of the entities
+/// nested in it are not its own, so only the root's `Link` children are taken.
+fn example_links(xml: &str) -> Vec<ExampleLink> {
+    let root = match xmltree::Element::parse(xml.as_bytes()) {
+        Ok(root) => root,
+        Err(_) => return Vec::new(),
+    };
+    let root_href = root.attributes.get("href");
+    root.children
+        .iter()
+        .filter_map(xmltree::XMLNode::as_element)
+        .filter(|element| element.name == "Link")
+        .filter_map(|element| {
+            let rel = element.attributes.get("rel")?;
+            let href = element.attributes.get("href")?;
+            Some(ExampleLink {
+                rel: rel.clone(),
+                media_type: element.attributes.get("type").cloned(),
+                href: href.clone(),
+                own: root_href.is_some_and(|root_href| {
+                    href == root_href || href.starts_with(&format!("{}/", root_href))
+                }),
+            })
+        })
+        .collect()
+}
+
+/// The path template an href leads to.
+fn match_path<'a>(href: &str, paths: &'a Paths) -> Option<&'a str> {
+    let path = href.split_once("/api/").map(|(_, p)| p)?;
+    match_template(path.split(['?', '#']).next()?, paths)
+}
+
+fn operations_mut(path_item: &mut PathItem) -> [Option<&mut Operation>; 8] {
+    [
+        path_item.get.as_mut(),
+        path_item.put.as_mut(),
+        path_item.post.as_mut(),
+        path_item.delete.as_mut(),
+        path_item.options.as_mut(),
+        path_item.head.as_mut(),
+        path_item.patch.as_mut(),
+        path_item.trace.as_mut(),
+    ]
+}
+
+/// Whether an operation takes or returns a schema.
+fn uses_schema(operation: &Operation, schema: &str) -> bool {
+    let request = operation
+        .request_body
+        .as_ref()
+        .and_then(ReferenceOr::as_item)
+        .map(|body| &body.content);
+    let responses = operation
+        .responses
+        .responses
+        .values()
+        .filter_map(ReferenceOr::as_item)
+        .map(|response| &response.content);
+    request
+        .into_iter()
+        .chain(responses)
+        .flat_map(|content| content.values())
+        .any(|media_type| schema_reference(media_type) == Some(schema))
+}
+
+/// The operation a relation stands for on a path: the only one there is, the
+/// method vCD uses for the relation, or else the only one using the schema the
+/// XSDs give the link's media type.
+fn target_operation<'a>(
+    path_item: &'a PathItem,
+    rel: &str,
+    schema: Option<&str>,
+) -> Option<&'a Operation> {
+    let available = operations(path_item)
+        .into_iter()
+        .filter_map(|(method, operation)| Some((method, operation?)))
+        .collect::<Vec<_>>();
+    if let [(_, operation)] = available.as_slice() {
+        return Some(operation);
+    }
+    let method = match rel {
+        "edit" => "put",
+        "remove" => "delete",
+        rel if GET_RELS.contains(&rel) => "get",
+        _ => "post",
+    };
+    if let Some((_, operation)) = available.iter().find(|(m, _)| *m == method) {
+        return Some(operation);
+    }
+    let using = available
+        .into_iter()
+        .filter(|(_, operation)| schema.is_some_and(|s| uses_schema(operation, s)))
+        .collect::<Vec<_>>();
+    match using.as_slice() {
+        [(_, operation)] => Some(operation),
+        _ => None,
+    }
+}
+
+/// The name of a link: its relation and, if it has one, the media type's subtype.
+fn link_name(rel: &str, media_type: Option<&str>) -> String {
+    let subtype = media_type
+        .and_then(|t| t.rsplit('.').next())
+        .map(|t| t.trim_end_matches("+xml").trim_end_matches("+json"));
+    let name = match subtype {
+        Some(subtype) => format!("{}_{}", rel, subtype),
+        None => rel.to_string(),
+    };
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+fn success_responses(operation: &mut Operation) -> impl Iterator<Item = &mut Response> {
+    operation
+        .responses
+        .responses
+        .iter_mut()
+        .filter(|(status, _)| match status {
+            StatusCode::Code(code) => (200..300).contains(code),
+            StatusCode::Range(range) => *range == 2,
+        })
+        .filter_map(|(_, response)| match response {
+            ReferenceOr::Item(response) => Some(response),
+            ReferenceOr::Reference { .. } => None,
+        })
+}
+
+fn schema_reference(media_type: &openapiv3::MediaType) -> Option<&str> {
+    match &media_type.schema {
+        Some(ReferenceOr::Reference { reference }) => Some(reference),
+        _ => None,
+    }
+}
+
+/// The links seen in the documented XML examples of each response schema.
+fn links_by_schema(
+    paths: &Paths,
+    media_types: &Value,
+) -> BTreeMap<String, IndexMap<String, LinkTarget>> {
+    let mut links = BTreeMap::<String, IndexMap<String, LinkTarget>>::new();
+    let bodies = paths
+        .paths
+        .values()
+        .filter_map(|path_item| match path_item {
+            ReferenceOr::Item(path_item) => Some(path_item),
+            ReferenceOr::Reference { .. } => None,
+        })
+        .flat_map(|path_item| operations(path_item).into_iter().filter_map(|(_, o)| o))
+        .flat_map(|operation| operation.responses.responses.values())
+        .filter_map(|response| match response {
+            ReferenceOr::Item(response) => Some(response),
+            ReferenceOr::Reference { .. } => None,
+        })
+        .flat_map(|response| response.content.values())
+        .filter_map(|media_type| {
+            let xml = media_type.extensions.get(XML_EXAMPLE_EXTENSION)?.as_str()?;
+            Some((schema_reference(media_type)?, xml))
+        });
+    for (schema, xml) in bodies {
+        for link in example_links(xml) {
+            let link_schema = link
+                .media_type
+                .as_deref()
+                .and_then(|t| media_types.get(media_type_key(t)))
+                .and_then(Value::as_str);
+            let target = match_path(&link.href, paths).and_then(|path| match &paths.paths[path] {
+                ReferenceOr::Item(path_item) => {
+                    target_operation(path_item, &link.rel, link_schema)?
+                        .operation_id
+                        .clone()
+                        .map(|operation_id| (path, operation_id))
+                }
+                ReferenceOr::Reference { .. } => None,
+            });
+            if let Some((path, operation_id)) = target {
+                links
+                    .entry(schema.to_string())
+                    .or_default()
+                    .entry(link_name(&link.rel, link.media_type.as_deref()))
+                    .or_insert(LinkTarget {
+                        rel: link.rel,
+                        media_type: link.media_type,
+                        path: path.to_string(),
+                        operation_id,
+                        own: link.own,
+                    });
+            }
+        }
+    }
+    links
+}
+
+/// A link to an operation. Path parameters are only passed on when the link
+/// leads to the entity the source path reads, or below it; any other target
+/// has to be taken from the link's `href`.
+fn link(source_path: &str, target: &LinkTarget) -> Link {
+    let own = target.own
+        && (target.path == source_path || target.path.starts_with(&format!("{}/", source_path)));
+    let parameters = if own {
+        let source_parameters = path_template_parameters(source_path);
+        path_template_parameters(&target.path)
+            .into_iter()
+            .filter(|p| source_parameters.contains(p))
+            .map(|(_, name)| (name.to_string(), json!(format!("$request.path.{}", name))))
+            .collect()
+    } else {
+        IndexMap::new()
+    };
+    let mut description = match &target.media_type {
+        Some(media_type) => format!(
+            "Follows the `{}` link of type `{}`.",
+            target.rel, media_type
+        ),
+        None => format!("Follows the `{}` link.", target.rel),
+    };
+    if parameters.is_empty() && !path_template_parameters(&target.path).is_empty() {
+        description.push_str(" Its path parameters are those of the link's `href`.");
+    }
+    Link {
+        description: Some(description),
+        operation: LinkOperation::OperationId(target.operation_id.clone()),
+        request_body: None,
+        parameters,
+        server: None,
+        extensions: std::iter::once(("x-vcloud-rel".to_string(), json!(target.rel)))
+            .chain(
+                target
+                    .media_type
+                    .iter()
+                    .map(|t| ("x-vcloud-media-type".to_string(), json!(t))),
+            )
+            .collect(),
+    }
+}
+
+/// Adds OpenAPI links to every response whose schema has `Link` elements in
+/// some example, leading to the operations the links' hrefs match.
+/// `media_types` maps the media types the XSDs annotate their types with to
+/// schema references, and picks the operation where the relation does not.
+pub fn links(paths: &mut Paths, media_types: &Value) {
+    let links = links_by_schema(paths, media_types);
+    for (path, path_item) in paths.paths.iter_mut() {
+        let path_item = match path_item {
+            ReferenceOr::Item(path_item) => path_item,
+            ReferenceOr::Reference { .. } => continue,
+        };
+        for operation in operations_mut(path_item).into_iter().flatten() {
+            for response in success_responses(operation) {
+                let targets = response
+                    .content
+                    .values()
+                    .filter_map(schema_reference)
+                    .filter_map(|schema| links.get(schema))
+                    .flatten()
+                    .map(|(name, target)| (name.clone(), target.clone()))
+                    .collect::<Vec<_>>();
+                for (name, target) in targets {
+                    response
+                        .links
+                        .entry(name)
+                        .or_insert_with(|| ReferenceOr::Item(link(path, &target)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn links_test() {
+    let operation = |operation_id: &str, example: Option<&str>| {
+        json!({
+            "operationId": operation_id,
+            "responses": {"200": {
+                "description": "OK",
+                "content": {
+                    "application/vnd.vmware.admin.test+json;version=36.0": {
+                        "schema": {"$ref": "#/components/schemas/vcloud_AdminTestType"},
+                        "x-vcloud-xml-example": example
+                    }
+                }
+            }}
+        })
+    };
+    let access = json!({
+        "application/vnd.vmware.vcloud.controlAccess+json;version=36.0": {
+            "schema": {"$ref": "#/components/schemas/vcloud_ControlAccessParamsType"}
+        }
+    });
+    let mut paths: Paths = serde_json::from_value(json!({
+        "/admin/test/{id}": {
+            "get": operation("getTest", Some(r#"<AdminTest xmlns="http://www.vmware.com/vcloud/v1.5" name="test" href="https://vcloud.example.com/api/admin/test/a1">
+                <Link rel="edit" type="application/vnd.vmware.admin.test+xml" href="https://vcloud.example.com/api/admin/test/a1"/>
+                <Link rel="remove" href="https://vcloud.example.com/api/admin/test/a1"/>
+                <Link rel="down" type="application/vnd.vmware.admin.test+xml" href="https://vcloud.example.com/api/admin/test/a2"/>
+                <Link rel="controlAccess" type="application/vnd.vmware.vcloud.controlAccess+xml" href="https://vcloud.example.com/api/admin/test/a1/controlAccess/"/>
+                <Link rel="up" type="application/vnd.vmware.admin.organization+xml" href="https://vcloud.example.com/api/admin/org/o1"/>
+                <Link rel="unknown" href="https://vcloud.example.com/api/nowhere"/>
+                <Children>
+                    <AdminTest name="child" href="https://vcloud.example.com/api/admin/test/a3">
+                        <Link rel="alternate" href="https://vcloud.example.com/api/admin/test/a3"/>
+                    </AdminTest>
+                </Children>
+            </AdminTest>"#)),
+            "put": operation("updateTest", None),
+            "delete": {"operationId": "deleteTest", "responses": {}}
+        },
+        "/admin/test/{id}/controlAccess/": {
+            "get": {"operationId": "getTestAccess", "responses": {"200": {"description": "OK", "content": access}}},
+            "put": {"operationId": "updateTestAccess", "responses": {}}
+        },
+        "/admin/org/{id}": {
+            "get": {"operationId": "getOrg", "responses": {}}
+        },
+        "/admin/org/{id}/tests": {
+            "post": operation("createTest", None)
+        }
+    }))
+    .unwrap();
+    links(
+        &mut paths,
+        &json!({
+            "application/vnd.vmware.admin.test": "#/components/schemas/vcloud_AdminTestType",
+            "application/vnd.vmware.vcloud.controlAccess": "#/components/schemas/vcloud_ControlAccessParamsType"
+        }),
+    );
+    let paths = serde_json::to_value(&paths).unwrap();
+    let test_links = json!({
+        "edit_test": {
+            "description": "Follows the `edit` link of type `application/vnd.vmware.admin.test+xml`.",
+            "operationId": "updateTest",
+            "parameters": {"id": "$request.path.id"},
+            "x-vcloud-rel": "edit",
+            "x-vcloud-media-type": "application/vnd.vmware.admin.test+xml"
+        },
+        "remove": {
+            "description": "Follows the `remove` link.",
+            "operationId": "deleteTest",
+            "parameters": {"id": "$request.path.id"},
+            "x-vcloud-rel": "remove"
+        },
+        "down_test": {
+            "description": "Follows the `down` link of type `application/vnd.vmware.admin.test+xml`. Its path parameters are those of the link's `href`.",
+            "operationId": "getTest",
+            "x-vcloud-rel": "down",
+            "x-vcloud-media-type": "application/vnd.vmware.admin.test+xml"
+        },
+        "controlAccess_controlAccess": {
+            "description": "Follows the `controlAccess` link of type `application/vnd.vmware.vcloud.controlAccess+xml`.",
+            "operationId": "getTestAccess",
+            "parameters": {"id": "$request.path.id"},
+            "x-vcloud-rel": "controlAccess",
+            "x-vcloud-media-type": "application/vnd.vmware.vcloud.controlAccess+xml"
+        },
+        "up_organization": {
+            "description": "Follows the `up` link of type `application/vnd.vmware.admin.organization+xml`. Its path parameters are those of the link's `href`.",
+            "operationId": "getOrg",
+            "x-vcloud-rel": "up",
+            "x-vcloud-media-type": "application/vnd.vmware.admin.organization+xml"
+        }
+    });
+    assert_eq!(
+        paths["/admin/test/{id}"]["get"]["responses"]["200"]["links"],
+        test_links
+    );
+    // Other operations on the entity returning the same schema get the links too.
+    assert_eq!(
+        paths["/admin/test/{id}"]["put"]["responses"]["200"]["links"],
+        test_links
+    );
+    // Elsewhere the entity's id is not in the path, so it comes from the href.
+    let created = &paths["/admin/org/{id}/tests"]["post"]["responses"]["200"]["links"];
+    assert_eq!(created["edit_test"].get("parameters"), None);
+    assert_eq!(
+        created["edit_test"]["description"],
+        "Follows the `edit` link of type `application/vnd.vmware.admin.test+xml`. Its path parameters are those of the link's `href`."
+    );
+}
+
+#[test]
+fn links_from_pages_test() {
+    use std::io::Write;
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, html) in [
+        (
+            "doc/operations/GET-Test.html",
+            include_str!("../parsers/doc/operations/GET-TestLinks.html"),
+        ),
+        (
+            "doc/operations/PUT-Test.html",
+            include_str!("../parsers/doc/operations/PUT-Test.html"),
+        ),
+    ] {
+        writer
+            .start_file(name, zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(html.as_bytes()).unwrap();
+    }
+    let mut zip = zip::ZipArchive::new(writer.finish().unwrap()).unwrap();
+    let content_type_mapping = BTreeMap::from([
+        (
+            "application/vnd.vmware.admin.test".to_string(),
+            "vcloud_AdminTestType".to_string(),
+        ),
+        (
+            "application/vnd.vmware.admin.testo".to_string(),
+            "vcloud_AdminTestTypeO".to_string(),
+        ),
+    ]);
+    let mut paths = crate::paths::paths(
+        &mut zip,
+        content_type_mapping.clone(),
+        BTreeMap::new(),
+        "36.0".into(),
+        &mut IndexMap::new(),
+        &crate::parsers::doc::etc::model::Model::default(),
+    )
+    .unwrap();
+    links(
+        &mut paths,
+        &crate::schemas::media_types(&content_type_mapping),
+    );
+    let paths = serde_json::to_value(&paths).unwrap();
+    assert_eq!(
+        paths["/admin/test/{id}"]["get"]["responses"]["200"]["links"],
+        json!({
+            "edit_test": {
+                "description": "Follows the `edit` link of type `application/vnd.vmware.admin.test+xml`.",
+                "operationId": "putTest",
+                "parameters": {"id": "$request.path.id"},
+                "x-vcloud-rel": "edit",
+                "x-vcloud-media-type": "application/vnd.vmware.admin.test+xml"
+            },
+            "down_test": {
+                "description": "Follows the `down` link of type `application/vnd.vmware.admin.test+xml`. Its path parameters are those of the link's `href`.",
+                "operationId": "getTest",
+                "x-vcloud-rel": "down",
+                "x-vcloud-media-type": "application/vnd.vmware.admin.test+xml"
+            }
+        })
+    );
+}
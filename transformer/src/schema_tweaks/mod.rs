@@ -1 +1,8 @@
+pub mod discriminators;
+pub mod header_parameters;
+pub mod links;
+pub mod path_parameters;
 pub mod query_parameters;
+pub mod query_records;
+pub mod query_results;
+pub mod response_headers;
@@ -0,0 +1,178 @@
+use openapiv3::{
+    Parameter, ParameterData, ParameterSchemaOrContent, ReferenceOr, Schema, SchemaKind,
+    StringType, Type, VariantOrUnknownOrEmpty,
+};
+#[cfg(test)]
+use serde_json::json;
+
+pub(crate) const UUID: &str = "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}";
+
+/// Identifier parameters, other than `id`, that take a bare UUID.
+const UUID_PARAMETERS: [&str; 5] = ["catalogId", "gatewayId", "orgId", "pvdcId", "vdcId"];
+
+/// Segments whose following `{id}` is a bare UUID. Other entities' ids have a
+/// prefix, such as `vm-`, or are URNs, so they're left untyped.
+const UUID_ID_SEGMENTS: [&str; 14] = [
+    "catalog",
+    "catalogItem",
+    "disk",
+    "edgeGateway",
+    "group",
+    "media",
+    "network",
+    "org",
+    "right",
+    "role",
+    "task",
+    "user",
+    "vdc",
+    "vdcStorageProfile",
+];
+
+/// The `(preceding segment, parameter name)` pairs of a templated path.
+pub fn path_template_parameters(path: &str) -> Vec<(&str, &str)> {
+    let segments = path.split('/').collect::<Vec<_>>();
+    segments
+        .windows(2)
+        .filter_map(|w| {
+            w[1].strip_prefix('{')
+                .and_then(|s| s.strip_suffix('}'))
+                .map(|name| (w[0], name))
+        })
+        .collect()
+}
+
+/// Name of the reusable parameter for `{name}` when it follows `segment` in a path.
+pub fn path_parameter_name(segment: &str, name: &str) -> String {
+    let segment = segment.trim_start_matches('{').trim_end_matches('}');
+    format!("path-parameter_{}_{}", segment, name)
+}
+
+pub fn path_parameter(segment: &str, name: &str, description: Option<String>) -> Parameter {
+    Parameter::Path {
+        parameter_data: ParameterData {
+            name: name.into(),
+            required: true,
+            description,
+            deprecated: None,
+            format: ParameterSchemaOrContent::Schema(ReferenceOr::Item(Schema {
+                schema_data: Default::default(),
+                schema_kind: SchemaKind::Type(Type::String(path_parameter_type(segment, name))),
+            })),
+            example: None,
+            examples: Default::default(),
+            explode: None,
+            extensions: Default::default(),
+        },
+        style: Default::default(),
+    }
+}
+
+fn path_parameter_type(segment: &str, name: &str) -> StringType {
+    match (segment, name) {
+        // vApps and VMs share the /vApp/{id} paths and are told apart by prefix.
+        ("vApp", "id") => StringType {
+            pattern: Some(format!("^(vapp|vm)-{}$", UUID)),
+            ..Default::default()
+        },
+        ("vAppTemplate", "id") => StringType {
+            pattern: Some(format!("^(vappTemplate|vm)-{}$", UUID)),
+            ..Default::default()
+        },
+        ("entity", "id") => StringType {
+            pattern: Some(format!("^urn:vcloud:[a-zA-Z]+:{}$", UUID)),
+            ..Default::default()
+        },
+        (segment, "id") if UUID_ID_SEGMENTS.contains(&segment) => StringType {
+            format: VariantOrUnknownOrEmpty::Unknown("uuid".to_owned()),
+            ..Default::default()
+        },
+        (_, name) if UUID_PARAMETERS.contains(&name) => StringType {
+            format: VariantOrUnknownOrEmpty::Unknown("uuid".to_owned()),
+            ..Default::default()
+        },
+        _ => StringType::default(),
+    }
+}
+
+#[test]
+fn vapp_path_parameter_test() {
+    assert_eq!(path_parameter_name("vApp", "id"), "path-parameter_vApp_id");
+    assert_eq!(
+        serde_json::to_value(path_parameter("vApp", "id", Some("The vApp.".into()))).unwrap(),
+        json!({
+            "in": "path",
+            "name": "id",
+            "description": "The vApp.",
+            "required": true,
+            "schema": {
+                "type": "string",
+                "pattern": format!("^(vapp|vm)-{}$", UUID)
+            },
+            "style": "simple"
+        })
+    );
+}
+
+#[test]
+fn uuid_path_parameter_test() {
+    assert_eq!(
+        serde_json::to_value(path_parameter("org", "id", None)).unwrap(),
+        json!({
+            "in": "path",
+            "name": "id",
+            "required": true,
+            "schema": {
+                "type": "string",
+                "format": "uuid"
+            },
+            "style": "simple"
+        })
+    );
+}
+
+#[test]
+fn unknown_id_path_parameter_test() {
+    assert_eq!(
+        serde_json::to_value(path_parameter("vdcComputePolicy", "id", None)).unwrap(),
+        json!({
+            "in": "path",
+            "name": "id",
+            "required": true,
+            "schema": {"type": "string"},
+            "style": "simple"
+        })
+    );
+}
+
+#[test]
+fn path_template_parameters_test() {
+    assert_eq!(
+        path_template_parameters("/admin/extension/vimServer/{id}/{vimObjType}/{vimObjMoref}"),
+        vec![
+            ("vimServer", "id"),
+            ("{id}", "vimObjType"),
+            ("{vimObjType}", "vimObjMoref")
+        ]
+    );
+}
+
+#[test]
+fn untyped_path_parameter_test() {
+    assert_eq!(
+        path_parameter_name("{domain}", "key"),
+        "path-parameter_domain_key"
+    );
+    assert_eq!(
+        serde_json::to_value(path_parameter("{domain}", "key", None)).unwrap(),
+        json!({
+            "in": "path",
+            "name": "key",
+            "required": true,
+            "schema": {
+                "type": "string"
+            },
+            "style": "simple"
+        })
+    );
+}
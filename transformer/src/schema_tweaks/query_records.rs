@@ -0,0 +1,66 @@
+use crate::parsers::doc::query::Query;
+use crate::spec::SCHEMA_PREFIX;
+use indexmap::IndexMap;
+use openapiv3::{ReferenceOr, Schema};
+use serde_json::{json, Map, Value};
+
+pub const QUERIES_EXTENSION: &str = "x-vcloud-queries";
+
+/// Returns the metadata for the `x-vcloud-queries` extension: the record and
+/// reference schemas of each query type and the attributes that can be used
+/// in `filter` and `sortAsc`/`sortDesc`. The result schemas of each format are
+/// added as `results` by [`query_results`](super::query_results::query_results).
+pub fn query_records(schemas: &IndexMap<String, ReferenceOr<Schema>>, queries: &[Query]) -> Value {
+    let mut metadata = Map::new();
+    for query in queries {
+        let mut entry = json!({
+            "filterable": query
+                .attributes
+                .iter()
+                .filter(|a| a.filterable)
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>(),
+            "sortable": query
+                .attributes
+                .iter()
+                .filter(|a| a.sortable)
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>(),
+        });
+        for (key, schema_name) in [
+            ("recordType", &query.record_type),
+            ("referenceType", &query.reference_type),
+        ] {
+            if let Some(schema_name) = schema_name.as_ref().filter(|s| schemas.contains_key(*s)) {
+                entry[key] = json!(format!("{}{}", SCHEMA_PREFIX, schema_name));
+            }
+        }
+        metadata.insert(query.name.clone(), entry);
+    }
+    Value::Object(metadata)
+}
+
+#[test]
+fn query_records_test() {
+    use std::convert::TryFrom;
+    let query = Query::try_from(include_str!("../parsers/doc/queries/adminVApp.html")).unwrap();
+    let mut schemas = IndexMap::new();
+    schemas.insert(
+        "vcloud_QueryResultAdminVAppRecordType".to_string(),
+        ReferenceOr::Item(Schema {
+            schema_data: Default::default(),
+            schema_kind: openapiv3::SchemaKind::Type(openapiv3::Type::Object(Default::default())),
+        }),
+    );
+    let metadata = query_records(&schemas, &[query]);
+    assert_eq!(
+        metadata,
+        json!({
+            "adminVApp": {
+                "recordType": "#/components/schemas/vcloud_QueryResultAdminVAppRecordType",
+                "filterable": ["name", "isEnabled", "creationDate", "org"],
+                "sortable": ["name", "numberOfVMs", "creationDate"]
+            }
+        })
+    );
+}
@@ -0,0 +1,385 @@
+use crate::parsers::doc::query::Query;
+use indexmap::IndexMap;
+use openapiv3::{
+    ArrayType, Discriminator, MediaType, ObjectType, Parameter, Paths, ReferenceOr, Schema,
+    SchemaData, SchemaKind, StatusCode, Type,
+};
+use serde_json::{json, Map, Value};
+
+/// The result formats a query can be asked for, with the schema of the
+/// container each one returns and the property holding the items.
+const FORMATS: [(&str, &str, &str); 3] = [
+    ("records", "vcloud_QueryResultRecordsType", "record"),
+    ("idrecords", "vcloud_QueryResultRecordsType", "record"),
+    ("references", "vcloud_ReferencesType", "reference"),
+];
+
+pub fn query_result_name(format: &str, query: &str) -> String {
+    format!("query-result-{}_{}", format, query)
+}
+
+fn reference(schema_name: &str) -> String {
+    format!("#/components/schemas/{}", schema_name)
+}
+
+/// The container of a format narrowed to the items of one query type.
+fn query_result(container: &str, property: &str, item: &str) -> Schema {
+    Schema {
+        schema_data: Default::default(),
+        schema_kind: SchemaKind::AllOf {
+            all_of: vec![
+                ReferenceOr::Reference {
+                    reference: reference(container),
+                },
+                ReferenceOr::Item(Schema {
+                    schema_data: Default::default(),
+                    schema_kind: SchemaKind::Type(Type::Object(ObjectType {
+                        properties: indexmap! {
+                            property.to_string() => ReferenceOr::boxed_item(Schema {
+                                schema_data: Default::default(),
+                                schema_kind: SchemaKind::Type(Type::Array(ArrayType {
+                                    items: Some(ReferenceOr::Reference {
+                                        reference: reference(item),
+                                    }),
+                                    min_items: None,
+                                    max_items: None,
+                                    unique_items: false,
+                                })),
+                            })
+                        },
+                        ..Default::default()
+                    })),
+                }),
+            ],
+        },
+    }
+}
+
+/// Adds the result schemas of every query type and format, returning them
+/// by query name and format.
+fn query_result_schemas(
+    schemas: &mut IndexMap<String, ReferenceOr<Schema>>,
+    queries: &[Query],
+) -> IndexMap<String, IndexMap<&'static str, String>> {
+    let mut results = IndexMap::new();
+    for query in queries {
+        let mut formats = IndexMap::new();
+        for (format, container, property) in FORMATS.iter() {
+            let item = match *format {
+                "references" => &query.reference_type,
+                _ => &query.record_type,
+            };
+            let item = match item.as_ref().filter(|i| schemas.contains_key(*i)) {
+                Some(item) => item,
+                None => continue,
+            };
+            let name = query_result_name(format, &query.name);
+            if !schemas.contains_key(&name) {
+                let schema = query_result(container, property, item);
+                schemas.insert(name.clone(), ReferenceOr::Item(schema));
+            }
+            formats.insert(*format, name);
+        }
+        if !formats.is_empty() {
+            results.insert(query.name.clone(), formats);
+        }
+    }
+    results
+}
+
+/// A schema for the result of any query type in a format, told apart by the
+/// container's `name`, which is the query type.
+fn any_query_result(
+    schemas: &mut IndexMap<String, ReferenceOr<Schema>>,
+    results: &IndexMap<String, IndexMap<&'static str, String>>,
+    format: &str,
+) -> Option<String> {
+    let mapping = results
+        .iter()
+        .filter_map(|(query, formats)| Some((query.clone(), reference(formats.get(format)?))))
+        .collect::<IndexMap<_, _>>();
+    if mapping.is_empty() {
+        return None;
+    }
+    let name = format!("query-result-{}", format);
+    schemas.insert(
+        name.clone(),
+        ReferenceOr::Item(Schema {
+            schema_data: SchemaData {
+                discriminator: Some(Discriminator {
+                    property_name: "name".to_string(),
+                    mapping: mapping.clone(),
+                    extensions: Default::default(),
+                }),
+                ..Default::default()
+            },
+            schema_kind: SchemaKind::OneOf {
+                one_of: mapping
+                    .values()
+                    .map(|reference| ReferenceOr::Reference {
+                        reference: reference.clone(),
+                    })
+                    .collect(),
+            },
+        }),
+    );
+    Some(name)
+}
+
+/// The query type a typed query path such as `/admin/users/query` runs.
+fn typed_query<'a>(path: &str, queries: &'a [Query]) -> Option<&'a str> {
+    let segment = path
+        .strip_suffix("/query")?
+        .rsplit('/')
+        .find(|s| !s.is_empty() && !s.starts_with('{'))?;
+    let admin = path.starts_with("/admin/");
+    [
+        segment.strip_suffix("References").unwrap_or(segment),
+        segment.strip_suffix("List").unwrap_or(segment),
+        segment.strip_suffix('s').unwrap_or(segment),
+    ]
+    .iter()
+    .flat_map(|base| {
+        let mut capitalized = base.chars();
+        let admin_name = capitalized
+            .next()
+            .map(|c| format!("admin{}{}", c.to_ascii_uppercase(), capitalized.as_str()));
+        admin_name
+            .filter(|_| admin)
+            .into_iter()
+            .chain(Some(base.to_string()))
+    })
+    .find_map(|name| {
+        queries
+            .iter()
+            .find(|q| q.name == name)
+            .map(|q| q.name.as_str())
+    })
+}
+
+fn has_parameter(parameters: &[ReferenceOr<Parameter>], name: &str) -> bool {
+    parameters.iter().any(|p| match p {
+        ReferenceOr::Item(Parameter::Query { parameter_data, .. }) => parameter_data.name == name,
+        _ => false,
+    })
+}
+
+/// Narrows the responses of the query operations to the schemas of the
+/// requested format and, where the path fixes it, query type. Returns the
+/// result schema of each query type by format, for the `x-vcloud-queries`
+/// extension.
+pub fn query_results(
+    paths: &mut Paths,
+    schemas: &mut IndexMap<String, ReferenceOr<Schema>>,
+    queries: &[Query],
+    api_version: &str,
+) -> Map<String, Value> {
+    let results = query_result_schemas(schemas, queries);
+    let any_results = FORMATS
+        .iter()
+        .filter_map(|(format, ..)| Some((*format, any_query_result(schemas, &results, format)?)))
+        .collect::<IndexMap<_, _>>();
+    for (path, path_item) in paths.paths.iter_mut() {
+        let operation = match path_item {
+            ReferenceOr::Item(openapiv3::PathItem {
+                get: Some(operation),
+                ..
+            }) => operation,
+            _ => continue,
+        };
+        if !has_parameter(&operation.parameters, "format") {
+            continue;
+        }
+        let formats = if has_parameter(&operation.parameters, "type") {
+            any_results.clone()
+        } else {
+            match typed_query(path, queries).and_then(|q| results.get(q)) {
+                Some(formats) => formats.clone(),
+                None => continue,
+            }
+        };
+        let response = operation
+            .responses
+            .responses
+            .iter_mut()
+            .find(|(status, _)| match status {
+                StatusCode::Code(code) => (200..300).contains(code),
+                StatusCode::Range(range) => *range == 2,
+            })
+            .and_then(|(_, response)| match response {
+                ReferenceOr::Item(response) => Some(response),
+                ReferenceOr::Reference { .. } => None,
+            });
+        if let Some(response) = response {
+            for (format, schema_name) in formats {
+                response.content.insert(
+                    format!(
+                        "application/vnd.vmware.vcloud.query.{}+json;version={}",
+                        format, api_version
+                    ),
+                    MediaType {
+                        schema: Some(ReferenceOr::Reference {
+                            reference: reference(&schema_name),
+                        }),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+    results
+        .iter()
+        .map(|(query, formats)| {
+            let formats = formats
+                .iter()
+                .map(|(format, schema_name)| (format.to_string(), json!(reference(schema_name))))
+                .collect();
+            (query.clone(), Value::Object(formats))
+        })
+        .collect()
+}
+
+#[test]
+fn query_results_test() {
+    let query = |name: &str, record: &str| Query {
+        name: name.to_string(),
+        description: None,
+        record_type: Some(format!("vcloud_{}", record)),
+        reference_type: Some("vcloud_ReferenceType".to_string()),
+        attributes: Vec::new(),
+    };
+    let queries = vec![
+        query("vm", "QueryResultVMRecordType"),
+        query("adminUser", "QueryResultAdminUserRecordType"),
+        query("task", "QueryResultTaskRecordType"),
+    ];
+    let mut schemas = IndexMap::new();
+    for name in [
+        "vcloud_QueryResultVMRecordType",
+        "vcloud_QueryResultAdminUserRecordType",
+        "vcloud_ReferenceType",
+    ] {
+        schemas.insert(
+            name.to_string(),
+            ReferenceOr::Item(Schema {
+                schema_data: Default::default(),
+                schema_kind: SchemaKind::Type(Type::Object(Default::default())),
+            }),
+        );
+    }
+    let query_operation = |parameters: &[&str]| {
+        json!({
+            "get": {
+                "parameters": parameters.iter().map(|p| json!({
+                    "in": "query",
+                    "name": p,
+                    "schema": {"$ref": format!("#/components/schemas/query-parameter_{}", p)},
+                    "style": "form"
+                })).collect::<Vec<_>>(),
+                "responses": {
+                    "200": {"description": "Returns OkResponse"},
+                    "4XX": {"$ref": "#/components/responses/error"},
+                    "5XX": {"$ref": "#/components/responses/error"}
+                }
+            }
+        })
+    };
+    let mut paths: Paths = serde_json::from_value(json!({
+        "/query": query_operation(&["type", "format"]),
+        "/admin/users/query": query_operation(&["format"]),
+        "/admin/tasks/query": query_operation(&["format"]),
+        "/vms": query_operation(&[]),
+    }))
+    .unwrap();
+    let results = query_results(&mut paths, &mut schemas, &queries, "36.0");
+    let paths = serde_json::to_value(&paths).unwrap();
+
+    assert_eq!(
+        paths["/admin/users/query"]["get"],
+        json!({
+            "parameters": query_operation(&["format"])["get"]["parameters"],
+            "responses": {
+                "200": {
+                    "description": "Returns OkResponse",
+                    "content": {
+                        "application/vnd.vmware.vcloud.query.records+json;version=36.0": {
+                            "schema": {"$ref": "#/components/schemas/query-result-records_adminUser"}
+                        },
+                        "application/vnd.vmware.vcloud.query.idrecords+json;version=36.0": {
+                            "schema": {"$ref": "#/components/schemas/query-result-idrecords_adminUser"}
+                        },
+                        "application/vnd.vmware.vcloud.query.references+json;version=36.0": {
+                            "schema": {"$ref": "#/components/schemas/query-result-references_adminUser"}
+                        }
+                    }
+                },
+                "4XX": {"$ref": "#/components/responses/error"},
+                "5XX": {"$ref": "#/components/responses/error"}
+            }
+        })
+    );
+    assert_eq!(
+        paths["/query"]["get"]["responses"]["200"]["content"]
+            ["application/vnd.vmware.vcloud.query.records+json;version=36.0"],
+        json!({"schema": {"$ref": "#/components/schemas/query-result-records"}})
+    );
+    assert_eq!(
+        paths["/admin/tasks/query"]["get"]["responses"]["200"]["content"],
+        json!({
+            "application/vnd.vmware.vcloud.query.references+json;version=36.0": {
+                "schema": {"$ref": "#/components/schemas/query-result-references_task"}
+            }
+        })
+    );
+    assert_eq!(paths["/vms"]["get"]["responses"]["200"].get("content"), None);
+    assert_eq!(
+        Value::Object(results),
+        json!({
+            "vm": {
+                "records": "#/components/schemas/query-result-records_vm",
+                "idrecords": "#/components/schemas/query-result-idrecords_vm",
+                "references": "#/components/schemas/query-result-references_vm"
+            },
+            "adminUser": {
+                "records": "#/components/schemas/query-result-records_adminUser",
+                "idrecords": "#/components/schemas/query-result-idrecords_adminUser",
+                "references": "#/components/schemas/query-result-references_adminUser"
+            },
+            "task": {"references": "#/components/schemas/query-result-references_task"}
+        })
+    );
+
+    assert_eq!(
+        serde_json::to_value(&schemas["query-result-records"]).unwrap(),
+        json!({
+            "discriminator": {
+                "propertyName": "name",
+                "mapping": {
+                    "vm": "#/components/schemas/query-result-records_vm",
+                    "adminUser": "#/components/schemas/query-result-records_adminUser"
+                }
+            },
+            "oneOf": [
+                {"$ref": "#/components/schemas/query-result-records_vm"},
+                {"$ref": "#/components/schemas/query-result-records_adminUser"}
+            ]
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&schemas["query-result-records_vm"]).unwrap(),
+        json!({
+            "allOf": [
+                {"$ref": "#/components/schemas/vcloud_QueryResultRecordsType"},
+                {
+                    "type": "object",
+                    "properties": {
+                        "record": {
+                            "type": "array",
+                            "items": {"$ref": "#/components/schemas/vcloud_QueryResultVMRecordType"}
+                        }
+                    }
+                }
+            ]
+        })
+    );
+}
@@ -0,0 +1,81 @@
+use indexmap::IndexMap;
+use openapiv3::{
+    Header, HeaderStyle, ParameterSchemaOrContent, ReferenceOr, Schema, SchemaKind, StringType,
+    Type, VariantOrUnknownOrEmpty,
+};
+
+/// Headers that OpenAPI describes elsewhere or that carry no API meaning.
+const IGNORED_HEADERS: [&str; 3] = ["Content-Length", "Content-Type", "Date"];
+
+const WELL_KNOWN_HEADERS: [(&str, &str); 4] = [
+    (
+        "x-vcloud-authorization",
+        "The legacy session token. Send it back in the `x-vcloud-authorization` request header.",
+    ),
+    (
+        "X-VMWARE-VCLOUD-ACCESS-TOKEN",
+        "The session's access token. Send it back as `Authorization: Bearer <token>`.",
+    ),
+    (
+        "X-VMWARE-VCLOUD-TOKEN-TYPE",
+        "The type of the access token, usually `Bearer`.",
+    ),
+    ("Location", "The URL of the created or affected resource."),
+];
+
+pub fn is_ignored_header(name: &str) -> bool {
+    IGNORED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name))
+}
+
+/// The reusable name of a header with a documented meaning.
+pub fn well_known_header_name(name: &str) -> Option<&'static str> {
+    WELL_KNOWN_HEADERS
+        .iter()
+        .map(|(h, _)| *h)
+        .find(|h| h.eq_ignore_ascii_case(name))
+}
+
+pub fn response_header(description: Option<String>, example: Option<String>) -> Header {
+    Header {
+        description,
+        style: HeaderStyle::Simple,
+        required: false,
+        deprecated: None,
+        format: ParameterSchemaOrContent::Schema(ReferenceOr::Item(Schema {
+            schema_data: Default::default(),
+            schema_kind: SchemaKind::Type(Type::String(StringType::default())),
+        })),
+        example: example.map(serde_json::Value::String),
+        examples: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+pub fn response_headers() -> IndexMap<String, ReferenceOr<Header>> {
+    WELL_KNOWN_HEADERS
+        .iter()
+        .map(|(name, description)| {
+            let mut header = response_header(Some(description.to_string()), None);
+            if *name == "Location" {
+                header.format = ParameterSchemaOrContent::Schema(ReferenceOr::Item(Schema {
+                    schema_data: Default::default(),
+                    schema_kind: SchemaKind::Type(Type::String(StringType {
+                        format: VariantOrUnknownOrEmpty::Unknown("uri".to_owned()),
+                        ..Default::default()
+                    })),
+                }));
+            }
+            (name.to_string(), ReferenceOr::Item(header))
+        })
+        .collect()
+}
+
+#[test]
+fn well_known_header_name_test() {
+    assert_eq!(
+        well_known_header_name("X-VMware-vCloud-Access-Token"),
+        Some("X-VMWARE-VCLOUD-ACCESS-TOKEN")
+    );
+    assert_eq!(well_known_header_name("X-Custom"), None);
+    assert!(is_ignored_header("content-type"));
+}
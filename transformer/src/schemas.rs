@@ -1,15 +1,21 @@
+use crate::parsers::doc::etc::{model::Model, namespace_prefix};
 use anyhow::Result;
 use indexmap::IndexMap;
-use openapiv3::{ReferenceOr, Schema};
+use openapiv3::{ReferenceOr, Schema, SchemaKind, Type};
+use serde_json::{json, Value};
 use std::collections::{BTreeMap};
 use std::convert::TryFrom;
 use std::io::{Read, Seek};
 
 use zip::read::ZipArchive;
 
+pub const MEDIA_TYPES_EXTENSION: &str = "x-vcloud-media-types";
+pub const TARGET_SCHEMA_EXTENSION: &str = "x-vcloud-target-schema";
+
 pub fn schemas<R: Read + Seek>(
     output: &mut IndexMap<String, ReferenceOr<Schema>>,
     zip: &mut ZipArchive<R>,
+    model: &mut Model,
 ) -> Result<BTreeMap<String, String>> {
     let mut type_file_names = zip
         .file_names()
@@ -38,19 +44,9 @@ pub fn schemas<R: Read + Seek>(
                         xmltree::XMLNode::Element(xmltree::Element { ref attributes, .. })
                             if attributes.contains_key("targetNamespace") =>
                         {
-                            attributes.get("targetNamespace").map(|t| match t.as_str() {
-                                "http://schemas.dmtf.org/ovf/envelope/1" => "ovf",
-                                "http://schemas.dmtf.org/ovf/environment/1" => "ovfenv",
-                                "http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_ResourceAllocationSettingData" => "rasd",
-                                "http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_VirtualSystemSettingData" => "vssd",
-                                "http://schemas.dmtf.org/wbem/wscim/1/common" => "cim",
-                                "http://www.vmware.com/vcloud/meta" => "meta",
-                                "http://www.vmware.com/schema/ovf" => "vmw",
-                                "http://www.vmware.com/vcloud/extension/v1.5" => "vcloud-ext",
-                                "http://www.vmware.com/vcloud/v1.5" => "vcloud",
-                                "http://www.vmware.com/vcloud/versions" => "versioning",
-                                _ => "vcloud",
-                            })
+                            attributes
+                                .get("targetNamespace")
+                                .map(|t| namespace_prefix(t).unwrap_or("vcloud"))
                         }
                         _ => None,
                     },
@@ -76,7 +72,109 @@ pub fn schemas<R: Read + Seek>(
                 }),
         );
         content_type_mapping.extend(xsd_schema.content_types_names());
+        model.extend(std::iter::once(xsd_schema));
+    }
+
+    for (type_name, property, target) in model.reference_targets(&content_type_mapping) {
+        if let Some(ReferenceOr::Item(schema)) = output.get_mut(&type_name) {
+            add_target_schema(schema, &property, &target);
+        }
     }
 
     Ok(content_type_mapping)
 }
+
+/// The `x-vcloud-media-types` extension: the schema of each media type, for
+/// resolving the `type` of references and links.
+pub fn media_types(content_type_mapping: &BTreeMap<String, String>) -> Value {
+    content_type_mapping
+        .iter()
+        .map(|(media_type, type_name)| {
+            (
+                media_type.clone(),
+                json!(format!("#/components/schemas/{}", type_name)),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Marks a reference-typed property with the schema of the resource it points to.
+///
+/// A `$ref` property is wrapped in an `allOf` so it can carry the extension.
+fn add_target_schema(schema: &mut Schema, property: &str, target: &str) {
+    let object = match &mut schema.schema_kind {
+        SchemaKind::Type(Type::Object(object)) => Some(object),
+        SchemaKind::AllOf { all_of } => all_of.iter_mut().rev().find_map(|s| match s {
+            ReferenceOr::Item(Schema {
+                schema_kind: SchemaKind::Type(Type::Object(object)),
+                ..
+            }) => Some(object),
+            _ => None,
+        }),
+        _ => None,
+    };
+    let property = match object.and_then(|o| o.properties.get_mut(property)) {
+        Some(property) => property,
+        None => return,
+    };
+    let target = json!(format!("#/components/schemas/{}", target));
+    match property {
+        ReferenceOr::Reference { reference } => {
+            *property = ReferenceOr::boxed_item(Schema {
+                schema_data: openapiv3::SchemaData {
+                    extensions: indexmap! { TARGET_SCHEMA_EXTENSION.into() => target },
+                    ..Default::default()
+                },
+                schema_kind: SchemaKind::AllOf {
+                    all_of: vec![ReferenceOr::Reference {
+                        reference: reference.clone(),
+                    }],
+                },
+            })
+        }
+        ReferenceOr::Item(schema) => {
+            schema
+                .schema_data
+                .extensions
+                .insert(TARGET_SCHEMA_EXTENSION.into(), target);
+        }
+    }
+}
+
+#[test]
+fn add_target_schema_test() {
+    let mut schema: Schema = serde_json::from_value(json!({
+        "allOf": [
+            {"$ref": "#/components/schemas/vcloud_ResourceEntityType"},
+            {
+                "type": "object",
+                "properties": {
+                    "vdc": {"$ref": "#/components/schemas/vcloud_ReferenceType"},
+                    "org": {
+                        "type": "array",
+                        "items": {"$ref": "#/components/schemas/vcloud_ReferenceType"}
+                    }
+                }
+            }
+        ]
+    }))
+    .unwrap();
+    add_target_schema(&mut schema, "vdc", "vcloud_VdcType");
+    add_target_schema(&mut schema, "org", "vcloud_OrgType");
+    add_target_schema(&mut schema, "missing", "vcloud_OrgType");
+    assert_eq!(
+        serde_json::to_value(&schema).unwrap()["allOf"][1]["properties"],
+        json!({
+            "vdc": {
+                "x-vcloud-target-schema": "#/components/schemas/vcloud_VdcType",
+                "allOf": [{"$ref": "#/components/schemas/vcloud_ReferenceType"}]
+            },
+            "org": {
+                "type": "array",
+                "items": {"$ref": "#/components/schemas/vcloud_ReferenceType"},
+                "x-vcloud-target-schema": "#/components/schemas/vcloud_OrgType"
+            }
+        })
+    );
+}
@@ -0,0 +1,60 @@
+//! Lookups into a generated spec shared by the tools working on one.
+
+use openapiv3::{Operation, PathItem, Paths};
+
+/// The prefix of references to component schemas.
+pub const SCHEMA_PREFIX: &str = "#/components/schemas/";
+
+/// A media type without its parameters, such as `;version=39.1`.
+pub fn bare_media_type(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or(media_type).trim()
+}
+
+/// The key of a media type in `x-vcloud-media-types`: the bare media type
+/// without its `+xml` or `+json` suffix.
+pub fn media_type_key(media_type: &str) -> &str {
+    let bare = bare_media_type(media_type);
+    bare.strip_suffix("+xml")
+        .or_else(|| bare.strip_suffix("+json"))
+        .unwrap_or(bare)
+}
+
+/// The path template a path below `/api` matches, preferring the one with the
+/// most literal segments in common.
+pub fn match_template<'a>(path: &str, paths: &'a Paths) -> Option<&'a str> {
+    let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    paths
+        .paths
+        .keys()
+        .filter_map(|template| {
+            let template_segments = template.trim_start_matches('/').split('/');
+            if template_segments.clone().count() != segments.len() {
+                return None;
+            }
+            let mut literals = 0;
+            for (t, s) in template_segments.zip(&segments) {
+                if t == *s {
+                    literals += 1;
+                } else if !t.starts_with('{') {
+                    return None;
+                }
+            }
+            Some((literals, template.as_str()))
+        })
+        .max_by_key(|(literals, _)| *literals)
+        .map(|(_, template)| template)
+}
+
+/// The operations of a path item, by method.
+pub fn operations(path_item: &PathItem) -> [(&'static str, Option<&Operation>); 8] {
+    [
+        ("get", path_item.get.as_ref()),
+        ("put", path_item.put.as_ref()),
+        ("post", path_item.post.as_ref()),
+        ("delete", path_item.delete.as_ref()),
+        ("options", path_item.options.as_ref()),
+        ("head", path_item.head.as_ref()),
+        ("patch", path_item.patch.as_ref()),
+        ("trace", path_item.trace.as_ref()),
+    ]
+}
@@ -0,0 +1,761 @@
+//! Validation of request and response bodies against the generated schemas.
+//!
+//! The schema of a body is found by its media type, first in the
+//! `x-vcloud-media-types` extension and then in the content of the
+//! operations. Inheritance through `allOf` is flattened, so properties of a
+//! parent are allowed where a part doesn't allow additional properties, and
+//! a `_type` discriminator picks the schema of the subtype it names.
+//!
+//! vCD writes `null` for unset properties, so `null` is only a violation for
+//! required properties.
+
+use crate::schemas::MEDIA_TYPES_EXTENSION;
+use crate::spec::{media_type_key, operations, SCHEMA_PREFIX};
+use indexmap::IndexMap;
+use openapiv3::{
+    AdditionalProperties, Discriminator, OpenAPI, ReferenceOr, Schema, SchemaKind, Type,
+};
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+
+/// What a violation is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ViolationKind {
+    /// No schema is known for the media type, or a reference is broken.
+    MissingSchema,
+    /// A value of the wrong JSON type, or a `null` that isn't allowed.
+    Type,
+    Enumeration,
+    Pattern,
+    /// A number, length or number of items out of bounds.
+    Range,
+    /// A `_type` the discriminator doesn't know, or of another type.
+    Discriminator,
+    /// A required property is missing or `null`.
+    Required,
+    /// A property the schema doesn't have.
+    Unexpected,
+}
+
+/// A way a body disagrees with its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// A JSON pointer to the value, empty for the whole body.
+    pub pointer: String,
+    /// The property of the object at `pointer` that is missing or unexpected.
+    pub property: Option<String>,
+    /// The named schema the value at `pointer` was checked against.
+    pub schema: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "body"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// The properties of an object schema and of the parts it inherits.
+#[derive(Default)]
+struct Properties<'a> {
+    properties: IndexMap<&'a str, &'a ReferenceOr<Box<Schema>>>,
+    required: Vec<&'a str>,
+    /// Whether a part disallows properties it doesn't list.
+    closed: bool,
+    additional: Option<&'a ReferenceOr<Schema>>,
+    discriminator: Option<&'a str>,
+}
+
+pub struct Validator<'a> {
+    spec: &'a OpenAPI,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(spec: &'a OpenAPI) -> Self {
+        Validator { spec }
+    }
+
+    /// The reference of the schema of a media type. Parameters such as the
+    /// version are ignored, and `+json` and `+xml` types share a schema.
+    pub fn schema_reference(&self, media_type: &str) -> Option<&'a str> {
+        let key = media_type_key(media_type);
+        let mapped = self
+            .spec
+            .components
+            .as_ref()
+            .and_then(|c| c.extensions.get(MEDIA_TYPES_EXTENSION))
+            .and_then(Value::as_object)
+            .and_then(|mapping| {
+                mapping
+                    .iter()
+                    .find(|(t, _)| t.eq_ignore_ascii_case(key))
+                    .and_then(|(_, reference)| reference.as_str())
+            });
+        mapped.or_else(|| {
+            self.spec
+                .paths
+                .paths
+                .values()
+                .filter_map(ReferenceOr::as_item)
+                .flat_map(|item| operations(item).into_iter().filter_map(|(_, o)| o))
+                .flat_map(|operation| {
+                    let request = operation
+                        .request_body
+                        .as_ref()
+                        .and_then(ReferenceOr::as_item)
+                        .map(|body| &body.content);
+                    let responses = operation
+                        .responses
+                        .responses
+                        .values()
+                        .filter_map(ReferenceOr::as_item)
+                        .map(|response| &response.content);
+                    request.into_iter().chain(responses)
+                })
+                .flatten()
+                .find_map(|(t, content)| match &content.schema {
+                    Some(ReferenceOr::Reference { reference })
+                        if media_type_key(t).eq_ignore_ascii_case(key) =>
+                    {
+                        Some(reference.as_str())
+                    }
+                    _ => None,
+                })
+        })
+    }
+
+    /// Validates a body of the media type against its schema.
+    pub fn validate(&self, media_type: &str, json: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        match self.schema_reference(media_type) {
+            Some(reference) => self.check_reference(reference, json, "", &mut violations),
+            None => violations.push(violation(
+                ViolationKind::MissingSchema,
+                "",
+                format!("no schema is known for {}", media_type),
+            )),
+        }
+        violations
+    }
+
+    /// Validates a body against a schema, such as an operation documents for
+    /// its content.
+    pub fn validate_schema(&self, schema: &'a ReferenceOr<Schema>, json: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.check(schema, json, "", &mut violations);
+        violations
+    }
+
+    fn schema(&self, reference: &str) -> Option<&'a Schema> {
+        let name = reference.strip_prefix(SCHEMA_PREFIX)?;
+        match self.spec.components.as_ref()?.schemas.get(name)? {
+            ReferenceOr::Item(schema) => Some(schema),
+            ReferenceOr::Reference { reference } => self.schema(reference),
+        }
+    }
+
+    /// The discriminator of a schema or of one of the parents it inherits.
+    fn discriminator(&self, schema: &'a Schema) -> Option<&'a Discriminator> {
+        schema
+            .schema_data
+            .discriminator
+            .as_ref()
+            .or_else(|| match &schema.schema_kind {
+                SchemaKind::AllOf { all_of } => all_of.iter().find_map(|part| match part {
+                    ReferenceOr::Reference { reference } => {
+                        self.discriminator(self.schema(reference)?)
+                    }
+                    ReferenceOr::Item(part) => self.discriminator(part),
+                }),
+                _ => None,
+            })
+    }
+
+    /// Whether the schema `reference` is `ancestor` or inherits from it.
+    fn extends(&self, reference: &str, ancestor: &str) -> bool {
+        reference == ancestor
+            || match self.schema(reference).map(|s| &s.schema_kind) {
+                Some(SchemaKind::AllOf { all_of }) => all_of.iter().any(|part| match part {
+                    ReferenceOr::Reference { reference } => self.extends(reference, ancestor),
+                    ReferenceOr::Item(_) => false,
+                }),
+                _ => false,
+            }
+    }
+
+    fn check_reference(
+        &self,
+        reference: &str,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let schema = match self.schema(reference) {
+            Some(schema) => schema,
+            None => {
+                return violations.push(violation(
+                    ViolationKind::MissingSchema,
+                    pointer,
+                    format!("the schema {} is missing", reference),
+                ))
+            }
+        };
+        let subtype = self
+            .discriminator(schema)
+            .and_then(|d| Some((d, value.get(&d.property_name)?.as_str()?)));
+        if let Some((discriminator, name)) = subtype {
+            match discriminator.mapping.get(name) {
+                Some(target) if target == reference => {}
+                Some(target) if self.extends(target, reference) => {
+                    return self.check_reference(target, value, pointer, violations)
+                }
+                Some(_) => {
+                    return violations.push(violation(
+                        ViolationKind::Discriminator,
+                        pointer,
+                        format!(
+                            "{} {} is not a kind of {}",
+                            discriminator.property_name,
+                            name,
+                            reference.trim_start_matches(SCHEMA_PREFIX)
+                        ),
+                    ))
+                }
+                None => {
+                    return violations.push(violation(
+                        ViolationKind::Discriminator,
+                        pointer,
+                        format!("unknown {} {}", discriminator.property_name, name),
+                    ))
+                }
+            }
+        }
+        let start = violations.len();
+        self.check_schema(schema, value, pointer, violations);
+        for violation in &mut violations[start..] {
+            if violation.schema.is_none() && violation.pointer == pointer {
+                violation.schema = Some(reference.to_string());
+            }
+        }
+    }
+
+    fn check(
+        &self,
+        schema: &ReferenceOr<Schema>,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        match schema {
+            ReferenceOr::Reference { reference } => {
+                self.check_reference(reference, value, pointer, violations)
+            }
+            ReferenceOr::Item(schema) => self.check_schema(schema, value, pointer, violations),
+        }
+    }
+
+    fn check_boxed(
+        &self,
+        schema: &ReferenceOr<Box<Schema>>,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        match schema {
+            ReferenceOr::Reference { reference } => {
+                self.check_reference(reference, value, pointer, violations)
+            }
+            ReferenceOr::Item(schema) => self.check_schema(schema, value, pointer, violations),
+        }
+    }
+
+    fn check_schema(
+        &self,
+        schema: &'a Schema,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        if value.is_null() {
+            if !schema.schema_data.nullable {
+                violations.push(violation(ViolationKind::Type, pointer, "is null".into()));
+            }
+            return;
+        }
+        match &schema.schema_kind {
+            // A lone `allOf` reference wraps the schema it refers to.
+            SchemaKind::AllOf { all_of } if all_of.len() == 1 && all_of[0].as_item().is_none() => {
+                self.check(&all_of[0], value, pointer, violations)
+            }
+            SchemaKind::Type(Type::String(s)) => {
+                let value = match value.as_str() {
+                    Some(value) => value,
+                    None => return violations.push(type_violation(pointer, "a string", value)),
+                };
+                if !s.enumeration.is_empty() && !s.enumeration.iter().flatten().any(|e| e == value)
+                {
+                    violations.push(violation(
+                        ViolationKind::Enumeration,
+                        pointer,
+                        format!("{:?} is not one of {}", value, one_of(&s.enumeration)),
+                    ));
+                }
+                if let Some(pattern) = &s.pattern {
+                    // XSD patterns match the whole value.
+                    match Regex::new(&format!("^(?:{})$", pattern)) {
+                        Ok(regex) if !regex.is_match(value) => violations.push(violation(
+                            ViolationKind::Pattern,
+                            pointer,
+                            format!("{:?} doesn't match {}", value, pattern),
+                        )),
+                        _ => {}
+                    }
+                }
+                let length = value.chars().count();
+                if s.min_length.is_some_and(|min| length < min)
+                    || s.max_length.is_some_and(|max| length > max)
+                {
+                    violations.push(violation(
+                        ViolationKind::Range,
+                        pointer,
+                        format!("{:?} has {} characters", value, length),
+                    ));
+                }
+            }
+            SchemaKind::Type(Type::Integer(i)) => {
+                let value = match value.as_i64() {
+                    Some(value) => value,
+                    None => return violations.push(type_violation(pointer, "an integer", value)),
+                };
+                if !i.enumeration.is_empty() && !i.enumeration.contains(&Some(value)) {
+                    violations.push(violation(
+                        ViolationKind::Enumeration,
+                        pointer,
+                        format!("{} is not one of {}", value, one_of(&i.enumeration)),
+                    ));
+                }
+                if i.minimum.is_some_and(|min| value < min)
+                    || i.maximum.is_some_and(|max| value > max)
+                {
+                    violations.push(violation(
+                        ViolationKind::Range,
+                        pointer,
+                        format!("{} is out of range", value),
+                    ));
+                }
+            }
+            SchemaKind::Type(Type::Number(n)) => {
+                let value = match value.as_f64() {
+                    Some(value) => value,
+                    None => return violations.push(type_violation(pointer, "a number", value)),
+                };
+                if n.minimum.is_some_and(|min| value < min)
+                    || n.maximum.is_some_and(|max| value > max)
+                {
+                    violations.push(violation(
+                        ViolationKind::Range,
+                        pointer,
+                        format!("{} is out of range", value),
+                    ));
+                }
+            }
+            SchemaKind::Type(Type::Boolean {}) => {
+                if !value.is_boolean() {
+                    violations.push(type_violation(pointer, "a boolean", value));
+                }
+            }
+            SchemaKind::Type(Type::Array(a)) => {
+                let items = match value.as_array() {
+                    Some(items) => items,
+                    None => return violations.push(type_violation(pointer, "an array", value)),
+                };
+                if a.min_items.is_some_and(|min| items.len() < min)
+                    || a.max_items.is_some_and(|max| items.len() > max)
+                {
+                    violations.push(violation(
+                        ViolationKind::Range,
+                        pointer,
+                        format!("has {} items", items.len()),
+                    ));
+                }
+                if let Some(schema) = &a.items {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check_boxed(schema, item, &format!("{}/{}", pointer, i), violations);
+                    }
+                }
+            }
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::AllOf { .. } => {
+                let mut properties = Properties::default();
+                self.collect(schema, &mut properties, value, pointer, violations);
+                if properties.closed
+                    || !properties.properties.is_empty()
+                    || matches!(schema.schema_kind, SchemaKind::Type(Type::Object(_)))
+                {
+                    self.check_object(&properties, value, pointer, violations);
+                }
+            }
+            SchemaKind::OneOf { one_of: schemas } | SchemaKind::AnyOf { any_of: schemas } => {
+                let mut first = None;
+                for schema in schemas {
+                    let mut attempt = Vec::new();
+                    self.check(schema, value, pointer, &mut attempt);
+                    if attempt.is_empty() {
+                        return;
+                    }
+                    first.get_or_insert(attempt);
+                }
+                violations.extend(first.into_iter().flatten());
+            }
+            SchemaKind::Not { .. } | SchemaKind::Any(_) => {}
+        }
+    }
+
+    /// Gathers the properties of an object schema and its `allOf` parts,
+    /// checking parts that aren't objects directly.
+    fn collect(
+        &self,
+        schema: &'a Schema,
+        properties: &mut Properties<'a>,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        if let Some(discriminator) = &schema.schema_data.discriminator {
+            properties.discriminator = Some(&discriminator.property_name);
+        }
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::Object(o)) => {
+                properties.properties.extend(
+                    o.properties
+                        .iter()
+                        .map(|(name, property)| (name.as_str(), property)),
+                );
+                properties
+                    .required
+                    .extend(o.required.iter().map(String::as_str));
+                match &o.additional_properties {
+                    Some(AdditionalProperties::Any(false)) => properties.closed = true,
+                    Some(AdditionalProperties::Schema(schema)) => {
+                        properties.additional = Some(schema)
+                    }
+                    _ => {}
+                }
+            }
+            SchemaKind::AllOf { all_of } => {
+                for part in all_of {
+                    let part = match part {
+                        ReferenceOr::Reference { reference } => match self.schema(reference) {
+                            Some(part) => part,
+                            None => {
+                                violations.push(violation(
+                                    ViolationKind::MissingSchema,
+                                    pointer,
+                                    format!("the schema {} is missing", reference),
+                                ));
+                                continue;
+                            }
+                        },
+                        ReferenceOr::Item(part) => part,
+                    };
+                    self.collect(part, properties, value, pointer, violations);
+                }
+            }
+            _ => self.check_schema(schema, value, pointer, violations),
+        }
+    }
+
+    fn check_object(
+        &self,
+        properties: &Properties<'a>,
+        value: &Value,
+        pointer: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => return violations.push(type_violation(pointer, "an object", value)),
+        };
+        for name in &properties.required {
+            if object.get(*name).unwrap_or(&Value::Null).is_null() {
+                violations.push(Violation {
+                    property: Some(name.to_string()),
+                    ..violation(
+                        ViolationKind::Required,
+                        pointer,
+                        format!("the required property {} is missing", name),
+                    )
+                });
+            }
+        }
+        for (name, property_value) in object {
+            let property_pointer =
+                format!("{}/{}", pointer, name.replace('~', "~0").replace('/', "~1"));
+            match (
+                properties.properties.get(name.as_str()),
+                properties.additional,
+            ) {
+                (Some(_), _) if property_value.is_null() => {}
+                (Some(property), _) => {
+                    self.check_boxed(property, property_value, &property_pointer, violations)
+                }
+                (None, Some(additional)) => {
+                    self.check(additional, property_value, &property_pointer, violations)
+                }
+                (None, None)
+                    if properties.closed && properties.discriminator != Some(name.as_str()) =>
+                {
+                    violations.push(Violation {
+                        property: Some(name.clone()),
+                        ..violation(
+                            ViolationKind::Unexpected,
+                            pointer,
+                            format!("the property {} is not in the schema", name),
+                        )
+                    })
+                }
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+fn violation(kind: ViolationKind, pointer: &str, message: String) -> Violation {
+    Violation {
+        kind,
+        pointer: pointer.to_string(),
+        property: None,
+        schema: None,
+        message,
+    }
+}
+
+fn one_of<T: fmt::Display>(enumeration: &[Option<T>]) -> String {
+    enumeration
+        .iter()
+        .flatten()
+        .map(T::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn type_violation(pointer: &str, expected: &str, value: &Value) -> Violation {
+    violation(
+        ViolationKind::Type,
+        pointer,
+        format!("expected {}, found {}", expected, value),
+    )
+}
+
+#[cfg(test)]
+use serde_json::json;
+
+#[cfg(test)]
+fn test_spec() -> OpenAPI {
+    serde_json::from_value(json!({
+        "openapi": "3.0.2",
+        "info": {"title": "Test API", "version": "36.0"},
+        "paths": {
+            "/query": {"get": {"responses": {"200": {"description": "Records", "content": {
+                "application/vnd.vmware.vcloud.query.records+json;version=36.0": {
+                    "schema": {"$ref": "#/components/schemas/vcloud_QueryResultRecordsType"}
+                }
+            }}}}}
+        },
+        "components": {
+            "x-vcloud-media-types": {
+                "application/vnd.vmware.vcloud.vApp": "#/components/schemas/vcloud_VAppType"
+            },
+            "schemas": {
+                "vcloud_ResourceType": {
+                    "type": "object",
+                    "properties": {
+                        "href": {"type": "string"},
+                        "name": {"type": "string", "pattern": "[a-z]+"}
+                    },
+                    "required": ["name"],
+                    "additionalProperties": false
+                },
+                "vcloud_VAppType": {
+                    "allOf": [
+                        {"$ref": "#/components/schemas/vcloud_ResourceType"},
+                        {
+                            "type": "object",
+                            "properties": {
+                                "status": {"type": "string", "enum": ["POWERED_ON", "POWERED_OFF"]},
+                                "memoryMB": {"type": "integer", "minimum": 1},
+                                "vm": {"type": "array", "items": {"$ref": "#/components/schemas/vcloud_ResourceType"}}
+                            },
+                            "additionalProperties": false
+                        }
+                    ]
+                },
+                "vcloud_QueryResultRecordsType": {
+                    "type": "object",
+                    "properties": {
+                        "record": {"type": "array", "items": {"$ref": "#/components/schemas/vcloud_QueryResultRecordType"}}
+                    }
+                },
+                "vcloud_QueryResultRecordType": {
+                    "discriminator": {
+                        "propertyName": "_type",
+                        "mapping": {
+                            "QueryResultRecordType": "#/components/schemas/vcloud_QueryResultRecordType",
+                            "QueryResultVMRecordType": "#/components/schemas/vcloud_QueryResultVMRecordType",
+                            "ResourceType": "#/components/schemas/vcloud_ResourceType"
+                        }
+                    },
+                    "type": "object",
+                    "properties": {"href": {"type": "string"}},
+                    "additionalProperties": false
+                },
+                "vcloud_QueryResultVMRecordType": {
+                    "allOf": [
+                        {"$ref": "#/components/schemas/vcloud_QueryResultRecordType"},
+                        {
+                            "type": "object",
+                            "properties": {"isDeployed": {"type": "boolean"}},
+                            "additionalProperties": false
+                        }
+                    ]
+                }
+            }
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn schema_reference_test() {
+    let spec = test_spec();
+    let validator = Validator::new(&spec);
+    assert_eq!(
+        validator.schema_reference("application/vnd.vmware.vcloud.vApp+json;version=36.0"),
+        Some("#/components/schemas/vcloud_VAppType")
+    );
+    assert_eq!(
+        validator.schema_reference("application/vnd.vmware.vcloud.vApp+xml"),
+        Some("#/components/schemas/vcloud_VAppType")
+    );
+    assert_eq!(
+        validator.schema_reference("application/vnd.vmware.vcloud.query.records+xml"),
+        Some("#/components/schemas/vcloud_QueryResultRecordsType")
+    );
+    assert_eq!(
+        validator.validate("application/json", &json!({})),
+        vec![violation(
+            ViolationKind::MissingSchema,
+            "",
+            "no schema is known for application/json".into()
+        )]
+    );
+}
+
+#[test]
+fn validate_test() {
+    let spec = test_spec();
+    let validator = Validator::new(&spec);
+    let validate = |json| {
+        validator
+            .validate(
+                "application/vnd.vmware.vcloud.vApp+json;version=36.0",
+                &json,
+            )
+            .iter()
+            .map(Violation::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    // Inherited properties are allowed and unset ones may be null.
+    assert_eq!(
+        validate(json!({
+            "name": "web",
+            "href": null,
+            "status": "POWERED_ON",
+            "memoryMB": 1024,
+            "vm": [{"name": "db"}]
+        })),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        validate(json!({
+            "name": "Web",
+            "status": "SUSPENDED",
+            "memoryMB": 0,
+            "vm": [{"href": 1}],
+            "owner": "me"
+        })),
+        vec![
+            "/name: \"Web\" doesn't match [a-z]+",
+            "/status: \"SUSPENDED\" is not one of POWERED_ON, POWERED_OFF",
+            "/memoryMB: 0 is out of range",
+            "/vm/0: the required property name is missing",
+            "/vm/0/href: expected a string, found 1",
+            "body: the property owner is not in the schema",
+        ]
+    );
+    assert_eq!(
+        validate(json!({"name": null})),
+        vec!["body: the required property name is missing"]
+    );
+    assert_eq!(
+        validate(json!([])),
+        vec!["body: expected an object, found []"]
+    );
+
+    assert_eq!(
+        validator.validate(
+            "application/vnd.vmware.vcloud.vApp+json",
+            &json!({"name": "web", "vm": [{"name": "db", "owner": "me"}]})
+        ),
+        vec![Violation {
+            property: Some("owner".into()),
+            schema: Some("#/components/schemas/vcloud_ResourceType".into()),
+            ..violation(
+                ViolationKind::Unexpected,
+                "/vm/0",
+                "the property owner is not in the schema".into()
+            )
+        }]
+    );
+}
+
+#[test]
+fn validate_discriminator_test() {
+    let spec = test_spec();
+    let validator = Validator::new(&spec);
+    let validate = |json| {
+        validator
+            .validate("application/vnd.vmware.vcloud.query.records+json", &json)
+            .iter()
+            .map(Violation::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        validate(json!({"record": [
+            {"_type": "QueryResultVMRecordType", "href": "h", "isDeployed": true},
+            {"_type": "QueryResultRecordType", "href": "h"}
+        ]})),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        validate(json!({"record": [
+            {"_type": "QueryResultRecordType", "isDeployed": true},
+            {"_type": "QueryResultVMRecordType", "isDeployed": "yes"},
+            {"_type": "ResourceType", "name": "web"},
+            {"_type": "VAppType"}
+        ]})),
+        vec![
+            "/record/0: the property isDeployed is not in the schema",
+            "/record/1/isDeployed: expected a boolean, found \"yes\"",
+            "/record/2: _type ResourceType is not a kind of vcloud_QueryResultRecordType",
+            "/record/3: unknown _type VAppType",
+        ]
+    );
+}